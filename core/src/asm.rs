@@ -0,0 +1,3253 @@
+use {
+    crate::pc::is_skipped,
+    proc_macro2::{Span, TokenStream as TokenStream2, TokenTree},
+    quote::{format_ident, quote, ToTokens},
+    std::{collections::HashMap, iter::FromIterator},
+    syn::{
+        parse_quote, spanned::Spanned, Data, DataStruct, DeriveInput, Field, GenericParam, Generics, Ident, Lit,
+        Meta, MetaList, MetaNameValue, NestedMeta, Type, Visibility,
+    },
+};
+
+/// rename_all 支持的规则
+const RENAME_RULES: [&str; 5] = [
+    "snake_case",
+    "camelCase",
+    "PascalCase",
+    "SCREAMING_SNAKE_CASE",
+    "kebab-case",
+];
+
+/// 按 rename_all 转换字段名, 字段名应为 snake_case, rule 应在 RENAME_RULES 中
+fn rename_column(name: &str, rule: &str) -> String {
+    let words = name.split('_').filter(|x| !x.is_empty()).collect::<Vec<&str>>();
+    let capitalize = |word: &str| {
+        let mut chars = word.chars();
+        match chars.next() {
+            Some(first) => first
+                .to_uppercase()
+                .chain(chars.flat_map(|c| c.to_lowercase()))
+                .collect(),
+            None => String::new(),
+        }
+    };
+
+    match rule {
+        "snake_case" => words
+            .iter()
+            .map(|x| x.to_lowercase())
+            .collect::<Vec<String>>()
+            .join("_"),
+        "camelCase" => words
+            .iter()
+            .enumerate()
+            .map(|(i, x)| if i == 0 { x.to_lowercase() } else { capitalize(x) })
+            .collect(),
+        "PascalCase" => words.iter().map(|x| capitalize(x)).collect(),
+        "SCREAMING_SNAKE_CASE" => words
+            .iter()
+            .map(|x| x.to_uppercase())
+            .collect::<Vec<String>>()
+            .join("_"),
+        "kebab-case" => words
+            .iter()
+            .map(|x| x.to_lowercase())
+            .collect::<Vec<String>>()
+            .join("-"),
+        _ => unreachable!("rename_all 已检查: {}", rule),
+    }
+}
+
+/// 转换 Lit
+fn lit_to_string(lit: &Lit) -> Option<String> {
+    match *lit {
+        Lit::Str(ref s) => Some(s.value()),
+        Lit::Int(ref i) => Some(i.base10_digits().to_string()),
+        Lit::Float(ref f) => Some(f.base10_digits().to_string()),
+        Lit::Bool(ref b) => Some(b.value.to_string()),
+        _ => None,
+    }
+}
+
+/// 检查 sql 中的名字, 仅允许字母数字下划线及 $, 不超过 64 字符, 非保留字
+fn check_sql_name(name: &str, allow_reserved: bool) -> Result<(), String> {
+    if name.is_empty() {
+        return Err("为空".to_string());
+    }
+    if name.chars().count() > 64 {
+        return Err("超过 64 字符".to_string());
+    }
+    if let Some(c) = name
+        .chars()
+        .find(|c| !(c.is_ascii_alphanumeric() || *c == '_' || *c == '$'))
+    {
+        return Err(format!("含非法字符 {:?}", c));
+    }
+    if !allow_reserved && RESERVED_WORDS.contains(&name.to_uppercase().as_str()) {
+        return Err("是保留字, 请用 name 改名, 或指定 allow_reserved".to_string());
+    }
+    Ok(())
+}
+
+/// 收集 tokens 中的全部 ident, 含 group 内部
+fn collect_idents(tokens: TokenStream2, idents: &mut Vec<String>) {
+    for token in tokens {
+        match token {
+            TokenTree::Ident(ident) => idents.push(ident.to_string()),
+            TokenTree::Group(group) => collect_idents(group.stream(), idents),
+            _ => {}
+        }
+    }
+}
+
+/// 泛型参数名, lifetime 不含 '
+fn generic_param_name(param: &GenericParam) -> String {
+    match param {
+        GenericParam::Type(x) => x.ident.to_string(),
+        GenericParam::Lifetime(x) => x.lifetime.ident.to_string(),
+        GenericParam::Const(x) => x.ident.to_string(),
+    }
+}
+
+/// 全部泛型参数名
+fn generic_param_names(generics: &Generics) -> Vec<String> {
+    generics.params.iter().map(generic_param_name).collect()
+}
+
+/// tokens 中是否用到 names 中的泛型参数
+fn uses_generics<T: ToTokens>(tokens: &T, names: &[String]) -> bool {
+    let mut idents = Vec::new();
+    collect_idents(tokens.to_token_stream(), &mut idents);
+    idents.iter().any(|ident| names.contains(ident))
+}
+
+/// Option<T> 中的 T
+fn option_inner(ty: &Type) -> Option<&Type> {
+    if let Type::Path(syn::TypePath { ref path, .. }) = *ty {
+        let segment = path.segments.last()?;
+        if segment.ident == "Option" {
+            if let syn::PathArguments::AngleBracketed(ref args) = segment.arguments {
+                if let Some(syn::GenericArgument::Type(ref inner)) = args.args.first() {
+                    return Some(inner);
+                }
+            }
+        }
+    }
+    None
+}
+
+/// 在全部字段上执行
+fn map_columns<M>(columns: &[Column], mut mapper: M) -> TokenStream2
+where
+    M: FnMut(usize, &Ident, &Type) -> TokenStream2,
+{
+    TokenStream2::from_iter(
+        columns
+            .iter()
+            .enumerate()
+            .map(|(i, x)| mapper(i, &x.ident, &x.field.ty)),
+    )
+}
+
+/// 在全部字段上执行并 join
+fn map_columns_and_join<F, M>(columns: &[Column], filter: F, mapper: M, sep: TokenStream2) -> TokenStream2
+where
+    F: FnMut(&&Column) -> bool,
+    M: FnMut(&Column) -> TokenStream2,
+{
+    TokenStream2::from_iter(
+        columns
+            .iter()
+            .filter(filter)
+            .map(mapper)
+            .enumerate()
+            .flat_map(|(i, x)| if i == 0 { vec![x] } else { vec![sep.clone(), x] }),
+    )
+}
+
+/// 生成的 sql 字符串
+#[derive(Debug)]
+pub struct SqlStrings {
+    pub create_table: String,
+    pub fields_b: String,
+    pub fields_bi: String,
+    pub fields_e: String,
+    pub fields_ei: String,
+    pub fields_p: String,
+    pub fields_pi: String,
+    pub fields_q: String,
+    pub fields_qc: String,
+}
+
+/// 解析 struct 及其属性
+fn parse_table(dvi: &DeriveInput) -> Result<Table, syn::Error> {
+    // 字段
+    let fields = match dvi.data {
+        Data::Struct(DataStruct { ref fields, .. }) => fields.iter().cloned().collect(),
+        _ => panic!("仅用于 struct"),
+    };
+
+    let mut table = Table::new();
+    table.parse_struct_derive(dvi);
+    table.parse_struct_fields(&fields)?;
+    table.parse_struct_generics(&dvi.generics);
+    table.check_names()?;
+    table.check_options()?;
+    Ok(table)
+}
+
+/// 合并全部错误, 无错误时返回 Ok
+fn combine_errors(errors: Vec<syn::Error>) -> Result<(), syn::Error> {
+    let mut errors = errors.into_iter();
+    match errors.next() {
+        Some(mut first) => {
+            for error in errors {
+                first.combine(error);
+            }
+            Err(first)
+        }
+        None => Ok(()),
+    }
+}
+
+/// make_create_table 及 make_fields_* 的 sql 字符串
+pub fn sql_strings(dvi: &DeriveInput) -> Result<SqlStrings, syn::Error> {
+    parse_table(dvi).map(|table| table.sql_strings())
+}
+
+/// 导出 schema 用的字段信息, 供 src/bin/schema.rs 使用
+#[derive(Debug)]
+pub struct ColumnSchema {
+    pub field: String,
+    pub name: String,
+    pub sql_type: String,
+    pub key: Option<String>,
+    pub auto: bool,
+    pub generated: Option<String>,
+    pub references: Option<(String, String)>,
+}
+
+/// 导出 schema 用的表信息, 供 src/bin/schema.rs 使用
+#[derive(Debug)]
+pub struct TableSchema {
+    pub struct_name: String,
+    pub name: String,
+    pub view: bool,
+    pub sharded: bool,
+    pub create_table: String,
+    pub create_history_table: Option<String>,
+    pub columns: Vec<ColumnSchema>,
+}
+
+/// 解析 struct, 得到导出 schema 用的表信息
+pub fn table_schema(dvi: &DeriveInput) -> Result<TableSchema, syn::Error> {
+    parse_table(dvi).map(|table| table.schema(&dvi.ident))
+}
+
+impl TableSchema {
+    /// 注册到 python_comm 的 inventory, 供 all_models() 使用
+    fn make_registry(&self, krate: &syn::Path) -> TokenStream2 {
+        let some = |x: &Option<String>| match x {
+            Some(x) => quote!(Some(#x)),
+            None => quote!(None),
+        };
+        let columns = self.columns.iter().map(|column| {
+            let ColumnSchema {
+                field,
+                name,
+                sql_type,
+                auto,
+                ..
+            } = column;
+            let key = some(&column.key);
+            let generated = some(&column.generated);
+            let references = match &column.references {
+                Some((table, id)) => quote!(Some((#table, #id))),
+                None => quote!(None),
+            };
+            quote!(
+                #krate::use_sql::ColumnInfo {
+                    field: #field,
+                    name: #name,
+                    sql_type: #sql_type,
+                    key: #key,
+                    auto: #auto,
+                    generated: #generated,
+                    references: #references,
+                }
+            )
+        });
+        let TableSchema {
+            struct_name,
+            name,
+            view,
+            sharded,
+            create_table,
+            ..
+        } = self;
+        let create_history_table = some(&self.create_history_table);
+
+        quote!(
+            #krate::inventory::submit! {
+                #krate::use_sql::ModelInfo {
+                    struct_name: #struct_name,
+                    table_name: #name,
+                    view: #view,
+                    sharded: #sharded,
+                    create_table: #create_table,
+                    create_history_table: #create_history_table,
+                    columns: &[#(#columns),*],
+                }
+            }
+        )
+    }
+}
+
+/// AsSqlModel 展开
+pub fn expand(dvi: &DeriveInput) -> TokenStream2 {
+    let table = match parse_table(dvi) {
+        Ok(table) => table,
+        Err(err) => return err.to_compile_error(),
+    };
+
+    // 类名
+    let struct_ident = &dvi.ident;
+    let (impl_generics, ty_generics, where_clause) = table.generics.split_for_impl();
+
+    // 无效的 crate 已由 check_options 报告
+    let krate: syn::Path = table
+        .opt
+        .get("crate")
+        .and_then(|x| syn::parse_str(x).ok())
+        .unwrap_or_else(|| parse_quote!(::python_comm));
+    let is_view = table.opt.contains_key("view");
+    let make_assign = table.make_assign();
+    // 泛型 model 没有确定的类型, 不注册
+    let make_registry = if cfg!(feature = "registry") && table.generics.params.is_empty() {
+        table.schema(struct_ident).make_registry(&krate)
+    } else {
+        quote!()
+    };
+    let SqlStrings {
+        create_table: make_create_table,
+        fields_b: make_fields_b,
+        fields_bi: make_fields_bi,
+        fields_e: make_fields_e,
+        fields_ei: make_fields_ei,
+        fields_p: make_fields_p,
+        fields_pi: make_fields_pi,
+        fields_q: make_fields_q,
+        fields_qc: make_fields_qc,
+    } = table.sql_strings();
+    let make_fields_bw = table.fields_string_w("`", "`", ", ", true); // 不含生成列
+    let all_columns = table.columns.iter().collect::<Vec<&Column>>();
+    let make_fields_c = Table::make_fields_c(&all_columns);
+    let make_fields_ee = table.make_fields_ee(true);
+    let make_fields_eei = table.make_fields_ee(false);
+    let make_diff = table.make_diff();
+    let make_fields_fi = table.make_fields_fi();
+    let make_skipped = table.make_skipped();
+    // 生成列由数据库计算, create_with 中取 Default
+    let make_generated_defaults = TokenStream2::from_iter(
+        table
+            .columns
+            .iter()
+            .filter(|column| column.is_generated())
+            .map(|column| {
+                let ident = &column.ident;
+                quote!(let #ident = Default::default();)
+            }),
+    );
+    let make_fields_from_row = table.make_fields_from_row(&all_columns);
+    let make_fields_v = table.make_fields_v(true);
+    // view 不可写入
+    let make_fields_vi = if is_view {
+        quote!(mysql::params::Params::Empty)
+    } else {
+        table.make_fields_v(false)
+    };
+    let make_projections = table.make_projections(&dvi.vis);
+    let make_validate = table.make_validate(&krate);
+    // 无校验规则时不生成 validation_errors, 不依赖 ValidationError
+    let validate_in = if table.has_validation() {
+        quote!(self.validate_joined().m(#krate::m!(fname))?;)
+    } else {
+        quote!()
+    };
+    let make_fake = table.make_fake();
+    let make_csv = if table.opt.contains_key("csv") {
+        table.make_csv()
+    } else {
+        quote!()
+    };
+    let make_fake_values = map_columns_and_join(
+        &table.columns,
+        |column| column.ident != "id" && !column.is_generated(),
+        |column| {
+            let ident = &column.ident;
+            quote!(obj.#ident.clone().into())
+        },
+        quote!(,),
+    );
+    let make_values = map_columns_and_join(
+        &table.columns,
+        |column| !column.is_generated(),
+        |column| {
+            let ident = &column.ident;
+            quote!(row.#ident.clone().into())
+        },
+        quote!(,),
+    );
+    let make_values_i = map_columns_and_join(
+        &table.columns,
+        |column| column.ident != "id" && !column.is_generated(),
+        |column| {
+            let ident = &column.ident;
+            quote!(row.#ident.clone().into())
+        },
+        quote!(,),
+    );
+    let make_table_name_for = table.make_table_name_for();
+    let id_name = table.id_name();
+    let id_param = table.id_param();
+    let where_id = format!("`{}`=:{}", id_name, id_param);
+    let id_ty = table.id_ty();
+    let make_expected_columns = map_columns_and_join(
+        &table.columns,
+        |_| true,
+        |column| {
+            let real = column.sql_name();
+            let sql_type = &column.sql_type;
+            quote!((#real, #sql_type))
+        },
+        quote!(,),
+    );
+    let (audit_update, audit_delete, make_create_history_table) = if table.opt.contains_key("audit") {
+        let history_sql = format!(
+            "INSERT INTO `{{}}_history` ({fields}, `op`, `changed_at`, `changed_by`) \
+             SELECT {fields}, :op, NOW(6), :changed_by FROM `{{}}` WHERE {where_id}",
+            fields = table.fields_string("`", "`", ", ", true),
+            where_id = where_id
+        );
+        let audit = |op: &str| {
+            quote!(
+                let name = self.table_name_for().m(#krate::m!(fname))?;
+                let v: Vec<(&str, mysql::Value)> = vec![
+                    (#id_param, self.id.clone().into()),
+                    ("op", #op.into()),
+                    ("changed_by", changed_by.into()),
+                ];
+                tx.exec_drop(format!(#history_sql, name, name), mysql::params::Params::from(v))
+                    .m(#krate::m!(fname))?;
+            )
+        };
+        let history = table.make_create_history_table();
+        (
+            audit("update"),
+            audit("delete"),
+            quote!(
+                /// 创建 history 表的 sql, 含全部字段及 op, changed_at, changed_by
+                pub fn make_create_history_table() -> &'static str {
+                    #history
+                }
+            ),
+        )
+    } else {
+        (quote!(), quote!(), quote!())
+    };
+    let page_sql = format!(
+        "SELECT {} FROM `{{}}`{{}} ORDER BY `{}` LIMIT {{}} OFFSET {{}}",
+        table.fields_string("`", "`", ", ", true),
+        id_name
+    );
+    let scan_sql = format!(
+        "SELECT {} FROM `{{}}` WHERE `{}` > :after ORDER BY `{}` LIMIT {{}}",
+        table.fields_string("`", "`", ", ", true),
+        id_name,
+        id_name
+    );
+    let before_insert = table.make_hook("before_insert", quote!(&mut self));
+    let after_insert = table.make_hook("after_insert", quote!(self, id));
+    let before_update = table.make_hook("before_update", quote!(self));
+    let after_load = table.make_hook("after_load", quote!(&mut obj));
+    let table_name = table.name.to_string();
+    // writer/reader 可为路径, 无效的值已由 check_options 报告
+    let pool = |x: &str| syn::parse_str::<syn::Path>(x).unwrap_or_else(|_| parse_quote!(WhoCreateDbPool));
+    let who = pool(table.opt.get("writer").unwrap_or(&table.who));
+    let reader = match table.opt.get("reader") {
+        Some(reader) => pool(reader),
+        None => who.clone(),
+    };
+
+    // 指定 reader 时, 写操作后当前线程的读操作暂时改用写 DbPool
+    let mark_primary_reads = match table.opt.get("reader") {
+        Some(_) => {
+            let ms: u64 = table
+                .opt
+                .get("primary_reads_ms")
+                .and_then(|x| x.parse().ok())
+                .unwrap_or(1000);
+            quote!(Self::force_primary_reads(std::time::Duration::from_millis(#ms));)
+        }
+        None => quote!(),
+    };
+
+    // SqlModel 的写操作, 同样调用钩子, before_insert/before_update 作用于副本
+    let make_model_writes = if is_view {
+        quote!(
+            /// view 只读, 拒绝写操作
+            fn create(&self) -> Result<Option<u64>, #krate::use_m::MoreError> {
+                let fname = "create";
+                Err(format!("view {} 只读", Self::table_name())).m(#krate::m!(fname))
+            }
+
+            /// view 只读, 拒绝写操作
+            fn update(&self) -> Result<(), #krate::use_m::MoreError> {
+                let fname = "update";
+                Err(format!("view {} 只读", Self::table_name())).m(#krate::m!(fname))
+            }
+
+            /// view 只读, 拒绝写操作
+            fn delete(&self) -> Result<(), #krate::use_m::MoreError> {
+                let fname = "delete";
+                Err(format!("view {} 只读", Self::table_name())).m(#krate::m!(fname))
+            }
+        )
+    } else {
+        let create = if table.opt.contains_key("before_insert") {
+            let before_insert = table.make_hook("before_insert", quote!(&mut obj));
+            quote!(
+                let mut obj = Clone::clone(self);
+                #before_insert
+                obj.insert_in(&mut *conn).m(#krate::m!(fname))
+            )
+        } else {
+            quote!(self.insert_in(&mut *conn).m(#krate::m!(fname)))
+        };
+        // audit 时 history 与 UPDATE/DELETE 在同一事务中执行
+        let audit = table.opt.contains_key("audit");
+        let conn = if audit { quote!(&mut tx) } else { quote!(&mut *conn) };
+        let in_tx = |body: TokenStream2| {
+            if audit {
+                quote!(
+                    let mut tx = conn.start_transaction(mysql::TxOpts::default()).m(#krate::m!(fname))?;
+                    #body?;
+                    tx.commit().m(#krate::m!(fname))
+                )
+            } else {
+                body
+            }
+        };
+        let update = if table.opt.contains_key("before_update") {
+            let before_update = table.make_hook("before_update", quote!(&mut obj));
+            quote!(
+                let mut obj = Clone::clone(self);
+                #before_update
+                obj.update_in(#conn, "").m(#krate::m!(fname))
+            )
+        } else {
+            quote!(self.update_in(#conn, "").m(#krate::m!(fname)))
+        };
+
+        // 仅在 python_comm 的缺省实现不够用时覆盖, 否则不依赖 SqlModel::update/delete 及 DbPool::get_conn
+        let custom = table.opt.contains_key("reader") || table.name.contains('{');
+        let update = in_tx(update);
+        let delete = in_tx(quote!(self.delete_tx_by(#conn, "").m(#krate::m!(fname))));
+        let validation = table.has_validation();
+        let create = if custom || validation || table.opt.contains_key("before_insert") || table.opt.contains_key("after_insert") {
+            quote!(
+                /// 依次调用 before_insert, validate, INSERT, after_insert
+                fn create(&self) -> Result<Option<u64>, #krate::use_m::MoreError> {
+                    let fname = "create";
+                    let mut db = Self::lock_writer().m(#krate::m!(fname))?;
+                    let mut conn = db.get_conn().m(#krate::m!(fname))?;
+                    #create
+                }
+            )
+        } else {
+            quote!()
+        };
+        let update = if custom || validation || audit || table.opt.contains_key("before_update") {
+            quote!(
+                /// 按 id 更新, 依次调用 before_update, validate, UPDATE, audit 时在同一事务中先将原记录写入 history 表
+                fn update(&self) -> Result<(), #krate::use_m::MoreError> {
+                    let fname = "update";
+                    let mut db = Self::lock_writer().m(#krate::m!(fname))?;
+                    let mut conn = db.get_conn().m(#krate::m!(fname))?;
+                    #update
+                }
+            )
+        } else {
+            quote!()
+        };
+        let delete = if custom || audit {
+            quote!(
+                /// 按 id 删除, audit 时在同一事务中先将原记录写入 history 表
+                fn delete(&self) -> Result<(), #krate::use_m::MoreError> {
+                    let fname = "delete";
+                    let mut db = Self::lock_writer().m(#krate::m!(fname))?;
+                    let mut conn = db.get_conn().m(#krate::m!(fname))?;
+                    #delete
+                }
+            )
+        } else {
+            quote!()
+        };
+        quote!(#create #update #delete)
+    };
+
+    // 写操作, view 不生成
+    let make_mutations = if is_view {
+        quote!()
+    } else {
+        quote!(
+        /// 保存, 同 SqlModel::create
+        pub fn create_with(#make_fields_fi) -> Result<Option<u64>, #krate::use_m::MoreError> {
+            let fname = "create_with";
+            let id = 0;
+            #make_generated_defaults
+            Self {#make_fields_c #make_skipped}.create().m(#krate::m!(fname))
+        }
+
+        /// 在指定连接或事务中保存
+        pub fn create_with_tx<Q: mysql::prelude::Queryable>(tx: &mut Q, #make_fields_fi) -> Result<Option<u64>, #krate::use_m::MoreError> {
+            let fname = "create_with_tx";
+            let id = 0;
+            #make_generated_defaults
+            Self {#make_fields_c #make_skipped}.create_tx(tx).m(#krate::m!(fname))
+        }
+
+        /// 在指定连接或事务中保存, 依次调用 before_insert, validate, INSERT, after_insert
+        #[allow(unused_mut)]
+        pub fn create_tx<Q: mysql::prelude::Queryable>(mut self, tx: &mut Q) -> Result<Option<u64>, #krate::use_m::MoreError> {
+            #before_insert
+            self.insert_in(tx)
+        }
+
+        /// validate, INSERT, after_insert, 由 create/create_tx 在 before_insert 后调用
+        fn insert_in<Q: mysql::prelude::Queryable>(&self, tx: &mut Q) -> Result<Option<u64>, #krate::use_m::MoreError> {
+            let fname = "insert_in";
+            #validate_in
+            let sql = format!(
+                "INSERT INTO `{}` ({}) VALUES ({})",
+                self.table_name_for().m(#krate::m!(fname))?,
+                Self::make_fields_bi(),
+                Self::make_fields_pi()
+            );
+            tx.exec_drop(sql, self.make_fields_vi()).m(#krate::m!(fname))?;
+            #mark_primary_reads
+            let id = tx
+                .query_first::<(u64,), _>("SELECT LAST_INSERT_ID()")
+                .m(#krate::m!(fname))?
+                .map(|(id,)| id);
+            #after_insert
+            Ok(id)
+        }
+
+        /// 在指定连接或事务中按 id 更新
+        pub fn update_tx<Q: mysql::prelude::Queryable>(&mut self, tx: &mut Q) -> Result<(), #krate::use_m::MoreError> {
+            self.update_tx_by(tx, "")
+        }
+
+        /// 同 update_tx, 依次调用 before_update, validate, UPDATE, changed_by 记录到 audit 的 history 表
+        pub fn update_tx_by<Q: mysql::prelude::Queryable>(
+            &mut self,
+            tx: &mut Q,
+            changed_by: &str,
+        ) -> Result<(), #krate::use_m::MoreError> {
+            #before_update
+            self.update_in(tx, changed_by)
+        }
+
+        /// validate, UPDATE, 由 update/update_tx_by 在 before_update 后调用
+        #[allow(unused_variables)]
+        fn update_in<Q: mysql::prelude::Queryable>(&self, tx: &mut Q, changed_by: &str) -> Result<(), #krate::use_m::MoreError> {
+            let fname = "update_in";
+            #validate_in
+            #audit_update
+            let sql = format!(
+                "UPDATE `{}` SET {} WHERE {}",
+                self.table_name_for().m(#krate::m!(fname))?,
+                Self::make_fields_ei(),
+                #where_id
+            );
+            tx.exec_drop(sql, self.make_fields_v()).m(#krate::m!(fname))?;
+            #mark_primary_reads
+            Ok(())
+        }
+
+        /// 在指定连接或事务中按 id 删除
+        pub fn delete_tx<Q: mysql::prelude::Queryable>(&self, tx: &mut Q) -> Result<(), #krate::use_m::MoreError> {
+            self.delete_tx_by(tx, "")
+        }
+
+        /// 同 delete_tx, changed_by 记录到 audit 的 history 表
+        #[allow(unused_variables)]
+        pub fn delete_tx_by<Q: mysql::prelude::Queryable>(
+            &self,
+            tx: &mut Q,
+            changed_by: &str,
+        ) -> Result<(), #krate::use_m::MoreError> {
+            let fname = "delete_tx_by";
+            #audit_delete
+            let sql = format!(
+                "DELETE FROM `{}` WHERE {}",
+                self.table_name_for().m(#krate::m!(fname))?,
+                #where_id
+            );
+            let v: Vec<(&str, mysql::Value)> = vec![(#id_param, self.id.clone().into())];
+            tx.exec_drop(sql, mysql::params::Params::from(v)).m(#krate::m!(fname))?;
+            #mark_primary_reads
+            Ok(())
+        }
+
+        )
+    };
+
+    // 以下方法名可能与字段的 setter 重名, 需在 table 中开启, 分表仅生成指定 table 名的版本
+    let sharded = table.name.contains('{');
+
+    // #[table(diff)]
+    let make_diff_fn = if table.opt.contains_key("diff") {
+        quote!(
+            /// 与 equal 相同的规则比较两个 obj, 返回全部不同的 (列名, self 值, other 值)
+            #[allow(clippy::neg_cmp_op_on_partial_ord)]
+            pub fn diff(&self, other: &Self) -> Vec<(&'static str, String, String)> {
+                #[allow(unused_mut)]
+                let mut diff = Vec::new();
+                #make_diff
+                diff
+            }
+        )
+    } else {
+        quote!()
+    };
+
+    // 有校验规则时生成 validate()
+    let make_validate_fn = if table.has_validation() {
+        quote!(
+            /// 校验各字段, 返回全部错误
+            pub fn validate(&self) -> Result<(), Vec<#krate::use_sql::ValidationError>> {
+                let errors = self.validation_errors();
+                if errors.is_empty() {
+                    Ok(())
+                } else {
+                    Err(errors)
+                }
+            }
+
+            /// 校验各字段, 返回全部错误
+            fn validation_errors(&self) -> Vec<#krate::use_sql::ValidationError> {
+                #make_validate
+            }
+
+            /// validate 的全部错误合并为一条, view 不使用
+            #[allow(dead_code)]
+            fn validate_joined(&self) -> Result<(), String> {
+                let errors = self.validation_errors();
+                if errors.is_empty() {
+                    Ok(())
+                } else {
+                    Err(errors.iter().map(|error| error.to_string()).collect::<Vec<String>>().join(", "))
+                }
+            }
+        )
+    } else {
+        quote!()
+    };
+
+    // #[table(fake)], view 不生成 fixture_insert_sql
+    let make_fake_fns = if table.opt.contains_key("fake") {
+        // 有校验规则时重新生成直到通过, 随机字符串难以匹配 regex, 未指定 fake 的 regex 字段不检查
+        let unchecked: Vec<&str> = table
+            .columns
+            .iter()
+            .filter(|column| column.opt.contains_key("regex") && !column.opt.contains_key("fake"))
+            .map(|column| column.sql_name())
+            .collect();
+        let retain = if unchecked.is_empty() {
+            quote!()
+        } else {
+            quote!(errors.retain(|error| !matches!(error.column, Some(column) if [#(#unchecked),*].contains(&column)));)
+        };
+        let fake_with = if table.has_validation() {
+            quote!(
+                let mut errors = Vec::new();
+                for _ in 0..100 {
+                    let obj = Self {
+                        #make_fake
+                        #make_skipped
+                    };
+                    errors = obj.validation_errors();
+                    #retain
+                    if errors.is_empty() {
+                        return obj;
+                    }
+                }
+                panic!(
+                    "{}::fake 100 次仍未通过 validate: {}",
+                    stringify!(#struct_ident),
+                    errors.iter().map(|x| x.to_string()).collect::<Vec<String>>().join(", ")
+                )
+            )
+        } else {
+            quote!(
+                Self {
+                    #make_fake
+                    #make_skipped
+                }
+            )
+        };
+        let fixture = if is_view {
+            quote!()
+        } else {
+            let unsharded = if sharded {
+                quote!()
+            } else {
+                quote!(
+                    /// n 条随机数据的 INSERT sql, 用于测试数据
+                    pub fn fixture_insert_sql(n: usize) -> String {
+                        Self::make_fixture_insert_sql(#table_name, n)
+                    }
+                )
+            };
+            quote!(
+                #unsharded
+
+                /// 同 fixture_insert_sql, 插入到指定的 table
+                pub fn fixture_insert_sql_for(name: &str, n: usize) -> Result<String, #krate::use_m::MoreError> {
+                    let fname = "fixture_insert_sql_for";
+                    Self::check_table_name(name).m(#krate::m!(fname))?;
+                    Ok(Self::make_fixture_insert_sql(name, n))
+                }
+
+                /// n 条随机数据的 INSERT sql, name 已检查
+                fn make_fixture_insert_sql(name: &str, n: usize) -> String {
+                    let mut rng = Self::fake_rng();
+                    let rows = (0..n)
+                        .map(|_| {
+                            let obj = Self::fake_with(&mut rng);
+                            let values: Vec<mysql::Value> = vec![#make_fake_values];
+                            format!(
+                                "({})",
+                                values.iter().map(|x| x.as_sql(false)).collect::<Vec<String>>().join(", ")
+                            )
+                        })
+                        .collect::<Vec<String>>();
+                    format!(
+                        "INSERT INTO `{}` ({}) VALUES {};",
+                        name,
+                        #make_fields_bi,
+                        rows.join(", ")
+                    )
+                }
+            )
+        };
+        quote!(
+            /// 随机生成一个符合字段类型、长度及 min/max 且通过 validate 的 obj, id 为 0
+            pub fn fake() -> Self {
+                Self::fake_with(&mut Self::fake_rng())
+            }
+
+            /// 用指定的随机数生成器生成通过 validate 的 obj, 其他类型字段取 Default, 100 次仍未通过时 panic
+            #[allow(unused_variables)]
+            pub fn fake_with<R: FnMut() -> u64>(rng: &mut R) -> Self {
+                #fake_with
+            }
+
+            /// fake 使用的 xorshift, 以时间及调用次数为种子, 同一时刻多次调用也不重复
+            fn fake_rng() -> impl FnMut() -> u64 {
+                static CALLS: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
+                let mut seed = (std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .map(|x| x.as_nanos() as u64)
+                    .unwrap_or(0)
+                    ^ CALLS.fetch_add(0x9e37_79b9_7f4a_7c15, std::sync::atomic::Ordering::Relaxed))
+                    | 1;
+                move || {
+                    seed ^= seed << 13;
+                    seed ^= seed >> 7;
+                    seed ^= seed << 17;
+                    seed
+                }
+            }
+
+            #fixture
+        )
+    } else {
+        quote!()
+    };
+
+    // #[table(page)]
+    let make_page_fns = if table.opt.contains_key("page") {
+        let unsharded = if sharded {
+            quote!()
+        } else {
+            quote!(
+                /// 在读 DbPool 中分页读取, filter 为 WHERE 条件 (可为空), params 为其参数, page 从 1 开始
+                pub fn page<P>(
+                    filter: &str,
+                    params: P,
+                    page: u64,
+                    per_page: u64,
+                ) -> Result<#krate::use_sql::Page<Self>, #krate::use_m::MoreError>
+                where
+                    P: Into<mysql::params::Params> + Clone,
+                {
+                    let fname = "page";
+                    let mut db = Self::lock().m(#krate::m!(fname))?;
+                    let mut conn = db.get_conn().m(#krate::m!(fname))?;
+                    Self::page_in(&mut *conn, #table_name, filter, params, page, per_page).m(#krate::m!(fname))
+                }
+            )
+        };
+        quote!(
+            #unsharded
+
+            /// 同 page, 在指定的连接或事务中读取指定的 table
+            pub fn page_in<Q, P>(
+                conn: &mut Q,
+                name: &str,
+                filter: &str,
+                params: P,
+                page: u64,
+                per_page: u64,
+            ) -> Result<#krate::use_sql::Page<Self>, #krate::use_m::MoreError>
+            where
+                Q: mysql::prelude::Queryable,
+                P: Into<mysql::params::Params> + Clone,
+            {
+                let fname = "page_in";
+                Self::check_table_name(name).m(#krate::m!(fname))?;
+                let page = page.max(1);
+                let per_page = per_page.max(1);
+                let filter = if filter.trim().is_empty() {
+                    String::new()
+                } else {
+                    format!(" WHERE {}", filter)
+                };
+
+                let total = conn
+                    .exec_first::<(u64,), _, _>(
+                        format!("SELECT COUNT(*) FROM `{}`{}", name, filter),
+                        params.clone(),
+                    )
+                    .m(#krate::m!(fname))?
+                    .map(|(total,)| total)
+                    .unwrap_or(0);
+                let items = conn
+                    .exec::<Self, _, _>(
+                        format!(#page_sql, name, filter, per_page, (page - 1).saturating_mul(per_page)),
+                        params,
+                    )
+                    .m(#krate::m!(fname))?;
+
+                Ok(#krate::use_sql::Page {
+                    items,
+                    total,
+                    page,
+                    per_page,
+                    pages: total / per_page + u64::from(total % per_page != 0),
+                })
+            }
+        )
+    } else {
+        quote!()
+    };
+
+    // #[table(scan)]
+    let make_scan_fns = if table.opt.contains_key("scan") {
+        let unsharded = if sharded {
+            quote!()
+        } else {
+            quote!(
+                /// 在读 DbPool 中按 id 顺序读取 id > after 的至多 batch_size 条, 用于遍历大表, 不使用 OFFSET
+                pub fn scan_by_id(after: #id_ty, batch_size: usize) -> Result<Vec<Self>, #krate::use_m::MoreError> {
+                    let fname = "scan_by_id";
+                    let mut db = Self::lock().m(#krate::m!(fname))?;
+                    let mut conn = db.get_conn().m(#krate::m!(fname))?;
+                    Self::scan_by_id_in(&mut *conn, #table_name, after, batch_size).m(#krate::m!(fname))
+                }
+
+                /// 在读 DbPool 中按 id 顺序逐条遍历全表, 每次读取 batch_size 条, 每批单独加锁
+                pub fn iter_all(batch_size: usize) -> impl Iterator<Item = Result<Self, #krate::use_m::MoreError>> {
+                    Self::iter_by_id(batch_size, Self::scan_by_id)
+                }
+            )
+        };
+        quote!(
+            #unsharded
+
+            /// 同 scan_by_id, 在指定的连接或事务中读取指定的 table
+            pub fn scan_by_id_in<Q: mysql::prelude::Queryable>(
+                conn: &mut Q,
+                name: &str,
+                after: #id_ty,
+                batch_size: usize,
+            ) -> Result<Vec<Self>, #krate::use_m::MoreError> {
+                let fname = "scan_by_id_in";
+                Self::check_table_name(name).m(#krate::m!(fname))?;
+                let v: Vec<(&str, mysql::Value)> = vec![("after", after.into())];
+                conn.exec::<Self, _, _>(format!(#scan_sql, name, batch_size), mysql::params::Params::from(v))
+                    .m(#krate::m!(fname))
+            }
+
+            /// 同 iter_all, 在指定的连接或事务中遍历指定的 table
+            pub fn iter_all_in<'c, Q: mysql::prelude::Queryable>(
+                conn: &'c mut Q,
+                name: &'c str,
+                batch_size: usize,
+            ) -> impl Iterator<Item = Result<Self, #krate::use_m::MoreError>> + 'c
+            where
+                Self: 'c,
+            {
+                Self::iter_by_id(batch_size, move |after, batch_size| {
+                    Self::scan_by_id_in(conn, name, after, batch_size)
+                })
+            }
+
+            /// 按 id 顺序逐条遍历, scan 读取 id > after 的至多 batch_size 条
+            fn iter_by_id<S>(batch_size: usize, mut scan: S) -> impl Iterator<Item = Result<Self, #krate::use_m::MoreError>>
+            where
+                S: FnMut(#id_ty, usize) -> Result<Vec<Self>, #krate::use_m::MoreError>,
+            {
+                let mut after: #id_ty = Default::default();
+                let mut batch = std::collections::VecDeque::new();
+                let mut done = false;
+                std::iter::from_fn(move || {
+                    if batch.is_empty() && !done {
+                        match scan(after.clone(), batch_size) {
+                            Ok(rows) => {
+                                done = rows.len() < batch_size;
+                                batch.extend(rows);
+                            }
+                            Err(err) => {
+                                done = true;
+                                return Some(Err(err));
+                            }
+                        }
+                    }
+                    let obj: Self = batch.pop_front()?;
+                    after = obj.id.clone();
+                    Some(Ok(obj))
+                })
+            }
+        )
+    } else {
+        quote!()
+    };
+
+    // #[table(verify_schema)]
+    let make_verify_fns = if table.opt.contains_key("verify_schema") {
+        let unsharded = if sharded {
+            quote!()
+        } else {
+            quote!(
+                /// 与读 DbPool 中的表结构比较, 返回 (字段名, 差异, 期望, 实际), 差异为 missing/extra/type/nullable/order
+                pub fn verify_schema() -> Result<Vec<(String, &'static str, String, String)>, #krate::use_m::MoreError> {
+                    let fname = "verify_schema";
+                    let mut db = Self::lock().m(#krate::m!(fname))?;
+                    let mut conn = db.get_conn().m(#krate::m!(fname))?;
+                    Self::verify_schema_for(&mut *conn, #table_name).m(#krate::m!(fname))
+                }
+            )
+        };
+        quote!(
+            #unsharded
+
+            /// 同 verify_schema, 在指定的连接中比较指定的 table
+            pub fn verify_schema_for<Q: mysql::prelude::Queryable>(
+                conn: &mut Q,
+                name: &str,
+            ) -> Result<Vec<(String, &'static str, String, String)>, #krate::use_m::MoreError> {
+                let fname = "verify_schema_for";
+                let columns: Vec<(String, String, String)> = conn
+                    .exec(
+                        "SELECT `COLUMN_NAME`, `COLUMN_TYPE`, `IS_NULLABLE` FROM `information_schema`.`COLUMNS` \
+                         WHERE `TABLE_SCHEMA`=DATABASE() AND `TABLE_NAME`=? ORDER BY `ORDINAL_POSITION`",
+                        (name,),
+                    )
+                    .m(#krate::m!(fname))?;
+                Ok(Self::compare_schema(&columns))
+            }
+
+            /// 比较按顺序排列的 (字段名, 类型, 是否可为 NULL), 即 information_schema.COLUMNS 的内容
+            pub fn compare_schema(columns: &[(String, String, String)]) -> Vec<(String, &'static str, String, String)> {
+                // MySQL 8 不再返回整数的显示宽度, bool 即 tinyint(1)
+                let normalize = |ty: &str| {
+                    let ty = ty.trim().to_lowercase();
+                    if ty == "bool" || ty == "boolean" || ty == "tinyint(1)" {
+                        return "tinyint(1)".to_string();
+                    }
+                    match ty.split_once('(') {
+                        Some((base, rest)) if base.ends_with("int") => {
+                            format!("{}{}", base, rest.split_once(')').map(|(_, x)| x).unwrap_or(""))
+                        }
+                        _ => ty,
+                    }
+                };
+                // text(n) 由 MySQL 按长度及字符集存为 tinytext/text/mediumtext/longtext 之一
+                let same_type = |expected: &str, actual: &str| {
+                    let (expected, actual) = (normalize(expected), normalize(actual));
+                    match expected.split_once('(') {
+                        Some(("text", _)) => ["tinytext", "text", "mediumtext", "longtext"].contains(&actual.as_str()),
+                        _ => expected == actual,
+                    }
+                };
+
+                let expected: &[(&str, &str)] = &[#make_expected_columns];
+                let mut report = Vec::new();
+                for (i, (column, ty)) in expected.iter().enumerate() {
+                    match columns.iter().position(|(x, _, _)| x == column) {
+                        Some(j) => {
+                            let (_, actual, nullable) = &columns[j];
+                            if !same_type(ty, actual) {
+                                report.push((column.to_string(), "type", ty.to_string(), actual.clone()));
+                            }
+                            if nullable != "NO" {
+                                report.push((column.to_string(), "nullable", "NO".to_string(), nullable.clone()));
+                            }
+                            if i != j {
+                                report.push((column.to_string(), "order", (i + 1).to_string(), (j + 1).to_string()));
+                            }
+                        }
+                        None => report.push((column.to_string(), "missing", ty.to_string(), String::new())),
+                    }
+                }
+                for (column, ty, _) in columns {
+                    if !expected.iter().any(|(x, _)| x == column) {
+                        report.push((column.clone(), "extra", String::new(), ty.clone()));
+                    }
+                }
+                report
+            }
+        )
+    } else {
+        quote!()
+    };
+
+    // #[table(load_data)]
+    let make_load_data_fns = if !is_view && table.opt.contains_key("load_data") {
+        let unsharded = if sharded {
+            quote!()
+        } else {
+            quote!(
+                /// LOAD DATA 导入 write_tsv 生成的文件, with_id 为 false 时不含 id, 由数据库生成
+                pub fn load_data_sql(path: &str, with_id: bool) -> String {
+                    Self::make_load_data_sql(#table_name, path, with_id)
+                }
+            )
+        };
+        quote!(
+            #unsharded
+
+            /// 同 load_data_sql, 导入到指定的 table
+            pub fn load_data_sql_for(name: &str, path: &str, with_id: bool) -> Result<String, #krate::use_m::MoreError> {
+                let fname = "load_data_sql_for";
+                Self::check_table_name(name).m(#krate::m!(fname))?;
+                Ok(Self::make_load_data_sql(name, path, with_id))
+            }
+
+            /// LOAD DATA 的 sql, name 已检查
+            fn make_load_data_sql(name: &str, path: &str, with_id: bool) -> String {
+                format!(
+                    "LOAD DATA LOCAL INFILE '{}' INTO TABLE `{}` CHARACTER SET utf8mb4 \
+                     FIELDS TERMINATED BY '\\t' ESCAPED BY '\\\\' LINES TERMINATED BY '\\n' ({})",
+                    path.replace('\\', "\\\\").replace('\'', "\\'"),
+                    name,
+                    if with_id { #make_fields_bw } else { #make_fields_bi }
+                )
+            }
+
+            /// 写入 LOAD DATA 使用的 tsv, 列同 load_data_sql, NULL 为 \N
+            pub fn write_tsv<W: std::io::Write>(rows: &[Self], writer: &mut W, with_id: bool) -> std::io::Result<()> {
+                for row in rows {
+                    let values: Vec<mysql::Value> = if with_id {
+                        vec![#make_values]
+                    } else {
+                        vec![#make_values_i]
+                    };
+                    let line = values
+                        .iter()
+                        .map(|value| match value {
+                            mysql::Value::NULL => "\\N".to_string(),
+                            mysql::Value::Bytes(bytes) => String::from_utf8_lossy(bytes)
+                                .replace('\\', "\\\\")
+                                .replace('\t', "\\t")
+                                .replace('\n', "\\n")
+                                .replace('\r', "\\r")
+                                .replace('\0', "\\0"),
+                            mysql::Value::Int(x) => x.to_string(),
+                            mysql::Value::UInt(x) => x.to_string(),
+                            mysql::Value::Date(y, m, d, h, i, s, us) => {
+                                format!("{:04}-{:02}-{:02} {:02}:{:02}:{:02}.{:06}", y, m, d, h, i, s, us)
+                            }
+                            other => other.as_sql(false).trim_matches('\'').to_string(),
+                        })
+                        .collect::<Vec<String>>()
+                        .join("\t");
+                    writeln!(writer, "{}", line)?;
+                }
+                Ok(())
+            }
+        )
+    } else {
+        quote!()
+    };
+
+    let impl_ast = quote!(
+        const _: () = {
+            #[allow(unused_imports)]
+            use #krate::use_m::*;
+            use #krate::use_sql::SqlModel as _;
+
+            impl #impl_generics #struct_ident #ty_generics #where_clause {
+                #make_mutations
+
+                #make_diff_fn
+
+                #make_validate_fn
+
+                /// 从 row 解码, 失败时返回出错的列名及原 row
+                pub fn try_from_row(mut row: mysql::Row) -> Result<Self, (&'static str, mysql::Row)> {
+                    #make_fields_from_row
+                    #[allow(unused_mut)]
+                    let mut obj = Self {
+                        #make_fields_c
+                        #make_skipped
+                    };
+                    #after_load
+                    Ok(obj)
+                }
+
+                /// 返回加锁的写 DbPool, 即 writer/who 属性指定的类名, 生成的写操作均使用
+                pub fn lock_writer() -> Result<std::sync::MutexGuard<'static, #krate::use_sql::DbPool>, #krate::use_m::MoreError> {
+                    let fname = "lock_writer";
+                    #who::lock().m(#krate::m!(fname))
+                }
+
+                /// 当前线程在 duration 内的读操作改用写 DbPool, 写操作后自动调用
+                pub fn force_primary_reads(duration: std::time::Duration) {
+                    Self::primary_reads_until().with(|until| until.set(Some(std::time::Instant::now() + duration)));
+                }
+
+                /// 当前线程读操作改用写 DbPool 的截止时间
+                fn primary_reads_until() -> &'static std::thread::LocalKey<std::cell::Cell<Option<std::time::Instant>>> {
+                    thread_local!(static UNTIL: std::cell::Cell<Option<std::time::Instant>> = std::cell::Cell::new(None));
+                    &UNTIL
+                }
+
+                /// 按 table 名模板中的 {xxx} 替换为 vars 中对应的值, 结果不合法时报错
+                pub fn table_name_with(vars: &[(&str, &str)]) -> Result<String, #krate::use_m::MoreError> {
+                    let fname = "table_name_with";
+                    let mut name = #table_name.to_string();
+                    for (key, value) in vars {
+                        name = name.replace(&format!("{{{}}}", key), value);
+                    }
+                    Self::check_table_name(&name).m(#krate::m!(fname))?;
+                    Ok(name)
+                }
+
+                /// 实际 table 名, 模板中的 {字段} 取自字段值, {yyyy}/{yyyymm}/{yyyymmdd} 取自 shard_by 字段
+                pub fn table_name_for(&self) -> Result<String, #krate::use_m::MoreError> {
+                    #make_table_name_for
+                }
+
+                /// 运行时得到的 table 名, 规则同编译时, 避免字段值等拼入 sql
+                fn check_table_name(name: &str) -> Result<(), String> {
+                    if name.is_empty() || name.chars().count() > 64 {
+                        return Err(format!("table 名 {:?} 为空或超过 64 字符", name));
+                    }
+                    match name.chars().find(|c| !(c.is_ascii_alphanumeric() || *c == '_' || *c == '$')) {
+                        Some(c) => Err(format!("table 名 {:?} 含非法字符 {:?}", name, c)),
+                        None => Ok(()),
+                    }
+                }
+
+                #make_fake_fns
+
+                #make_csv
+
+                #make_create_history_table
+
+                #make_page_fns
+
+                #make_scan_fns
+
+                #make_verify_fns
+
+                #make_load_data_fns
+
+                /// 以指定 table 名创建表的 sql
+                pub fn make_create_table_for(name: &str) -> Result<String, #krate::use_m::MoreError> {
+                    let fname = "make_create_table_for";
+                    Self::check_table_name(name).m(#krate::m!(fname))?;
+                    Ok(#make_create_table.replacen(&format!("`{}`", #table_name), &format!("`{}`", name), 1))
+                }
+
+                #make_assign
+            }
+
+            impl #impl_generics #krate::use_sql::SqlModel for #struct_ident #ty_generics #where_clause {
+                /// 比较两个 obj
+                fn equal(&self, other: &Self) -> bool {
+                    #make_fields_ee
+                }
+
+                /// 比较两个 obj, 排除 id
+                fn equal_without_id(&self, other: &Self) -> bool {
+                    #make_fields_eei
+                }
+
+                /// 返回加锁的读 DbPool, 即 reader 属性指定的类名, 未指定时同 lock_writer()
+                ///
+                /// 当前线程写入后或调用 force_primary_reads() 后的有效期内, 改为返回写 DbPool
+                fn lock() -> Result<std::sync::MutexGuard<'static, #krate::use_sql::DbPool>, #krate::use_m::MoreError> {
+                    let fname = "lock";
+                    let force = Self::primary_reads_until().with(|until| match until.get() {
+                        Some(until) => std::time::Instant::now() < until,
+                        None => false,
+                    });
+                    if force {
+                        #who::lock().m(#krate::m!(fname))
+                    } else {
+                        #reader::lock().m(#krate::m!(fname))
+                    }
+                }
+
+                #make_model_writes
+
+                fn make_create_table() -> &'static str {
+                    #make_create_table
+                }
+
+                fn make_fields_b() -> &'static str {
+                    #make_fields_b
+                }
+
+                fn make_fields_bi() -> &'static str {
+                    #make_fields_bi
+                }
+
+                fn make_fields_e() -> &'static str {
+                    #make_fields_e
+                }
+
+                fn make_fields_ei() -> &'static str {
+                    #make_fields_ei
+                }
+
+                fn make_fields_p() -> &'static str {
+                    #make_fields_p
+                }
+
+                fn make_fields_pi() -> &'static str {
+                    #make_fields_pi
+                }
+
+                fn make_fields_q() -> &'static str {
+                    #make_fields_q
+                }
+
+                fn make_fields_qc() -> &'static str {
+                    #make_fields_qc
+                }
+
+                fn make_fields_v(&self) -> mysql::params::Params {
+                    #make_fields_v
+                }
+
+                fn make_fields_vi(&self) -> mysql::params::Params {
+                    #make_fields_vi
+                }
+
+                fn table_name() -> &'static str {
+                    #table_name
+                }
+            }
+
+            impl #impl_generics mysql::prelude::FromRow for #struct_ident #ty_generics #where_clause {
+                /// conn.exec::<Self>() 等走这里, 失败时报告出错的列名
+                fn from_row(row: mysql::Row) -> Self {
+                    match Self::try_from_row(row) {
+                        Ok(x) => x,
+                        Err((column, row)) => panic!("{} 解码列 {} 失败: {:?}", stringify!(#struct_ident), column, row),
+                    }
+                }
+
+                fn from_row_opt(row: mysql::Row) -> Result<Self, mysql::FromRowError> {
+                    Self::try_from_row(row).map_err(|(_, row)| mysql::FromRowError(row))
+                }
+            }
+
+            #make_registry
+
+        };
+
+        #make_projections
+    );
+
+    // 仅用于调试
+    // eprintln!("{}", impl_ast);
+
+    impl_ast
+}
+
+struct Column {
+    field: Field,                 // 字段
+    ident: Ident,                 // 字段
+    name: String,                 // 字段名
+    name_span: Span,              // 字段名位置, 用于报错
+    spans: HashMap<String, Span>, // 选项位置, 用于报错
+    sql_type: String,             // sql 类型
+    opt: HashMap<String, String>, // 选项, auto, key, name ...
+}
+
+impl Column {
+    /// 转换 rust 类型为 sql 类型
+    fn convert_rust_type_to_sql(type_: &str) -> String {
+        let type_ = TYPE_MAP1
+            .iter()
+            .find_map(|(x, y)| if x == &type_ { Some(*y) } else { None })
+            .unwrap_or(type_);
+
+        TYPE_MAP2
+            .iter()
+            .find_map(|(x, y)| if x == &type_ { Some(*y) } else { None })
+            .unwrap_or(type_)
+            .to_string()
+    }
+
+    /// 构造
+    fn new(field: Field, opt: HashMap<String, String>, spans: HashMap<String, Span>) -> Self {
+        let ident = field.ident.clone().unwrap();
+        let sql_type = opt
+            .get("type")
+            .cloned()
+            .unwrap_or_else(|| Self::convert_rust_type_to_sql(&field.ty.to_token_stream().to_string()));
+        let name = ident.to_string();
+        let name_span = spans.get("name").copied().unwrap_or_else(|| ident.span());
+
+        Self {
+            field,
+            ident,
+            name,
+            name_span,
+            spans,
+            sql_type,
+            opt,
+        }
+    }
+
+    /// 选项的位置, 未记录时取字段名位置
+    fn option_span(&self, key: &str) -> Span {
+        self.spans.get(key).copied().unwrap_or(self.name_span)
+    }
+
+    /// 在 sql 中的 auto 属性
+    fn sql_auto(&self) -> &str {
+        match self.opt.get("auto") {
+            Some(_) => " AUTO_INCREMENT",
+            None => "",
+        }
+    }
+
+    /// 在 sql 中的 generated 属性
+    fn sql_generated(&self) -> String {
+        match self.opt.get("generated") {
+            Some(expr) => format!(
+                " GENERATED ALWAYS AS ({}){}",
+                expr,
+                if self.opt.contains_key("stored") {
+                    " STORED"
+                } else {
+                    " VIRTUAL"
+                }
+            ),
+            None => String::new(),
+        }
+    }
+
+    /// 生成列, 不可写入
+    fn is_generated(&self) -> bool {
+        self.opt.contains_key("generated")
+    }
+
+    /// 在 sql 中的 references 属性, "user(id)" 或 "user", 缺省引用 id
+    fn sql_references(&self) -> Option<(String, String)> {
+        self.opt.get("references").map(|x| match x.split_once('(') {
+            Some((table, column)) => (
+                table.trim().to_string(),
+                column.trim_end_matches(')').trim().to_string(),
+            ),
+            None => (x.trim().to_string(), "id".to_string()),
+        })
+    }
+
+    /// 在 sql 中的 key 属性
+    fn sql_key(&self) -> Option<String> {
+        match self.opt.get("key") {
+            Some(key) => {
+                if key.is_empty() {
+                    Some(format!("KEY (`{}`)", self.sql_name()))
+                } else {
+                    Some(format!("{} KEY (`{}`)", key, self.sql_name()))
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// 在 sql 中的名字
+    fn sql_name(&self) -> &str {
+        self.opt.get("name").unwrap_or(&self.name)
+    }
+
+    /// 作为命名参数的名字, :xxx 仅允许字母数字下划线
+    fn param_name(&self) -> String {
+        self.sql_name()
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() || c == '_' { c } else { '_' })
+            .collect()
+    }
+
+    /// self.a==other.a, eq="skip" 时不比较, eq_epsilon 时按误差比较
+    fn eq_expr(&self) -> Option<TokenStream2> {
+        let ident = &self.ident;
+
+        if self.opt.get("eq").map(|x| x == "skip").unwrap_or(false) {
+            return None;
+        }
+
+        // 无效的 eq_epsilon 已由 check_options 报告
+        match self.opt.get("eq_epsilon").map(|x| x.parse::<f64>()) {
+            Some(Ok(epsilon)) => Some(quote!(((self.#ident as f64) - (other.#ident as f64)).abs() <= #epsilon)),
+            _ => Some(quote!(self.#ident == other.#ident)),
+        }
+    }
+}
+
+struct Table {
+    name: String,                             // table 名
+    name_span: Span,                          // table 名位置, 用于报错
+    spans: HashMap<String, Span>,             // 选项位置, 用于报错
+    who: String,                              // WhoCreateDbPool 类名
+    opt: HashMap<String, String>,             // 选项, from_row ...
+    projections: Vec<(String, String, Span)>, // 投影, (struct 名, 字段列表, 位置)
+    generics: Generics,                       // 泛型, 含字段类型的约束
+    columns: Vec<Column>,                     // 字段
+    skipped: Vec<Ident>,                      // #[pc(skip)] 的字段
+}
+
+/// @TODO Refactor duplicated code
+impl Table {
+    /// 从 column meta 中解析属性
+    fn extract_column_meta(meta_items: &Vec<&NestedMeta>) -> HashMap<String, String> {
+        let mut opt = HashMap::new();
+
+        for meta_item in meta_items {
+            match **meta_item {
+                NestedMeta::Meta(Meta::NameValue(MetaNameValue { ref path, ref lit, .. })) => {
+                    opt.insert(
+                        path.get_ident().unwrap().to_string(),
+                        lit_to_string(lit).unwrap_or_default(),
+                    );
+                }
+                // 标志, 如 not_empty
+                NestedMeta::Meta(Meta::Path(ref path)) => {
+                    opt.insert(path.get_ident().unwrap().to_string(), String::new());
+                }
+                _ => {}
+            }
+        }
+
+        opt
+    }
+
+    /// 从 table meta 中解析属性
+    fn extract_table_meta(&mut self, meta_items: &Vec<&NestedMeta>) {
+        self.name = "unknown_table_name".to_string();
+
+        for meta_item in meta_items {
+            match **meta_item {
+                NestedMeta::Meta(Meta::NameValue(MetaNameValue { ref path, ref lit, .. })) => {
+                    match path.get_ident().unwrap().to_string().as_ref() {
+                        "name" => {
+                            self.name = lit_to_string(lit).unwrap_or_default();
+                            self.name_span = lit.span();
+                        }
+                        "who" => {
+                            self.who = lit_to_string(lit).unwrap_or_default();
+                            self.spans.insert("who".to_string(), lit.span());
+                        }
+                        other => {
+                            self.opt
+                                .insert(other.to_string(), lit_to_string(lit).unwrap_or_default());
+                            self.spans.insert(other.to_string(), lit.span());
+                        }
+                    }
+                }
+                NestedMeta::Meta(Meta::List(MetaList {
+                    ref path, ref nested, ..
+                })) if path.get_ident().unwrap() == "projection" => {
+                    let opt = Self::extract_column_meta(&nested.iter().collect());
+                    // 缺少的项留空, 由 check_options 报错
+                    let name = opt.get("name").cloned().unwrap_or_default();
+                    let columns = opt.get("columns").cloned().unwrap_or_default();
+                    self.projections.push((name, columns, meta_item.span()));
+                }
+                // 标志, 如 allow_reserved
+                NestedMeta::Meta(Meta::Path(ref path)) => {
+                    self.opt.insert(path.get_ident().unwrap().to_string(), String::new());
+                    self.spans.insert(path.get_ident().unwrap().to_string(), path.span());
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// 选项的位置, 未记录时取 table 名位置
+    fn option_span(&self, key: &str) -> Span {
+        self.spans.get(key).copied().unwrap_or(self.name_span)
+    }
+
+    /// 检查 table 名及字段名, 避免生成错误或危险的 sql
+    fn check_names(&self) -> Result<(), syn::Error> {
+        let mut errors = Vec::new();
+
+        // table 名允许含模板 {xxx}
+        if let Err(message) = check_sql_name(
+            &self.name.replace(['{', '}'], ""),
+            self.opt.contains_key("allow_reserved"),
+        ) {
+            errors.push(syn::Error::new(
+                self.name_span,
+                format!("table 名 {:?} {}", self.name, message),
+            ));
+        }
+
+        // prefix 单独检查, 出错时字段名仅检查其余部分
+        let prefix = self.opt.get("prefix").cloned().unwrap_or_default();
+        let mut checked_prefix = prefix.as_str();
+        if !prefix.is_empty() {
+            if let Err(message) = check_sql_name(&prefix, true) {
+                errors.push(syn::Error::new(
+                    self.option_span("prefix"),
+                    format!("prefix {:?} {}", prefix, message),
+                ));
+                checked_prefix = "";
+            }
+        }
+        let kebab = self.opt.get("rename_all").map(|x| x.as_str()) == Some("kebab-case");
+
+        for column in &self.columns {
+            let allow_reserved = column.opt.contains_key("allow_reserved");
+            // rename_all="kebab-case" 生成的 - 在反引号中是安全的, 命名参数使用 param_name, prefix 中的 - 不在此列
+            let name = match column.opt.get("name") {
+                Some(name) => name.clone(),
+                None => {
+                    let rest = &column.name[prefix.len()..];
+                    if kebab {
+                        format!("{}{}", checked_prefix, rest.replace('-', "_"))
+                    } else {
+                        format!("{}{}", checked_prefix, rest)
+                    }
+                }
+            };
+            if let Err(message) = check_sql_name(&name, allow_reserved) {
+                errors.push(syn::Error::new(
+                    column.name_span,
+                    format!("字段名 {:?} {}", column.sql_name(), message),
+                ));
+            }
+            if let Some((table, name)) = column.sql_references() {
+                for x in [&table, &name] {
+                    if let Err(message) = check_sql_name(x, allow_reserved) {
+                        errors.push(syn::Error::new(
+                            column.name_span,
+                            format!("references {:?} {}", x, message),
+                        ));
+                    }
+                }
+            }
+        }
+
+        combine_errors(errors)
+    }
+
+    /// 检查选项的值, 避免生成代码时 panic
+    fn check_options(&self) -> Result<(), syn::Error> {
+        let mut errors = Vec::new();
+
+        // shard_by 及 table 名模板, 日期模板缺少 shard_by 时报在 table 名上
+        let shard_by = self.opt.get("shard_by");
+        if let Some(shard_by) = shard_by {
+            if !self.columns.iter().any(|column| column.ident == shard_by) {
+                errors.push(syn::Error::new(
+                    self.option_span("shard_by"),
+                    format!("shard_by 字段 {} 不存在", shard_by),
+                ));
+            }
+        }
+        for key in self.template_keys() {
+            match self.template_column(&key) {
+                Err(_) if shard_by.is_some() && matches!(key.as_str(), "yyyy" | "yyyymm" | "yyyymmdd") => {}
+                Err(message) => errors.push(syn::Error::new(self.name_span, message)),
+                Ok(_) => {}
+            }
+        }
+
+        for (name, columns, span) in &self.projections {
+            if let Err(message) = self.projection(name, columns) {
+                errors.push(syn::Error::new(*span, message));
+            }
+        }
+
+        // 函数路径及数字
+        let path = |key: &str, value: &str, span: Span| {
+            syn::parse_str::<syn::Path>(value)
+                .err()
+                .map(|_| syn::Error::new(span, format!("{} 应为函数路径, 而不是 {:?}", key, value)))
+        };
+        let number = |key: &str, value: &str, span: Span| {
+            value
+                .parse::<f64>()
+                .err()
+                .map(|_| syn::Error::new(span, format!("{} 应为数字, 而不是 {:?}", key, value)))
+        };
+        if self.opt.contains_key("view") && self.opt.contains_key("audit") {
+            errors.push(syn::Error::new(
+                self.option_span("audit"),
+                "view 为只读, 不能与 audit 同时使用",
+            ));
+        }
+
+        if let Some(value) = self.opt.get("crate") {
+            if syn::parse_str::<syn::Path>(value).is_err() {
+                errors.push(syn::Error::new(
+                    self.option_span("crate"),
+                    format!("crate 应为路径, 而不是 {:?}", value),
+                ));
+            }
+        }
+
+        // DbPool 的类名, 可为路径
+        if syn::parse_str::<syn::Path>(&self.who).is_err() {
+            errors.push(syn::Error::new(
+                self.option_span("who"),
+                format!("who 应为类名或路径, 而不是 {:?}", self.who),
+            ));
+        }
+        for key in ["writer", "reader"] {
+            if let Some(value) = self.opt.get(key) {
+                if syn::parse_str::<syn::Path>(value).is_err() {
+                    errors.push(syn::Error::new(
+                        self.option_span(key),
+                        format!("{} 应为类名或路径, 而不是 {:?}", key, value),
+                    ));
+                }
+            }
+        }
+        if let Some(value) = self.opt.get("primary_reads_ms") {
+            if value.parse::<u64>().is_err() {
+                errors.push(syn::Error::new(
+                    self.option_span("primary_reads_ms"),
+                    format!("primary_reads_ms 应为整数毫秒, 而不是 {:?}", value),
+                ));
+            }
+        }
+        for key in ["validate", "before_insert", "after_insert", "before_update", "after_load"] {
+            if let Some(value) = self.opt.get(key) {
+                errors.extend(path(key, value, self.option_span(key)));
+            }
+        }
+
+        for column in &self.columns {
+            for key in ["fake", "validate"] {
+                if let Some(value) = column.opt.get(key) {
+                    errors.extend(path(key, value, column.option_span(key)));
+                }
+            }
+            for key in ["min", "max", "eq_epsilon"] {
+                if let Some(value) = column.opt.get(key) {
+                    errors.extend(number(key, value, column.option_span(key)));
+                }
+            }
+        }
+
+        combine_errors(errors)
+    }
+
+    /// 快速设置每个字段
+    fn make_assign(&self) -> TokenStream2 {
+        map_columns(&self.columns, |_i, ident, ty| {
+            quote!(
+                pub fn #ident<__T>(mut self, v: __T) -> Self
+                where
+                    __T: Into<#ty>,
+                {
+                    self.#ident = v.into();
+                    self
+                }
+            )
+        })
+    }
+
+    /// 导出 schema 用的表信息
+    fn schema(&self, struct_ident: &Ident) -> TableSchema {
+        TableSchema {
+            struct_name: struct_ident.to_string(),
+            name: self.name.clone(),
+            view: self.opt.contains_key("view"),
+            sharded: self.name.contains('{'),
+            create_table: self.make_create_table(),
+            create_history_table: if self.opt.contains_key("audit") {
+                Some(self.make_create_history_table())
+            } else {
+                None
+            },
+            columns: self
+                .columns
+                .iter()
+                .map(|column| ColumnSchema {
+                    field: column.ident.to_string(),
+                    name: column.sql_name().to_string(),
+                    sql_type: column.sql_type.clone(),
+                    key: column.opt.get("key").cloned(),
+                    auto: column.opt.contains_key("auto"),
+                    generated: column.opt.get("generated").cloned(),
+                    references: column.sql_references(),
+                })
+                .collect(),
+        }
+    }
+
+    /// 创建表的 sql
+    fn make_create_table(&self) -> String {
+        // view
+        if let Some(select) = self.opt.get("view") {
+            return format!("CREATE VIEW `{}` AS {};", self.name, select);
+        }
+
+        // 字段定义
+        let mut lines = self
+            .columns
+            .iter()
+            .map(|column| {
+                format!(
+                    "`{}` {}{} NOT NULL{}",
+                    column.sql_name(),
+                    column.sql_type,
+                    column.sql_generated(),
+                    column.sql_auto()
+                )
+            })
+            .collect::<Vec<String>>();
+
+        // 键
+        lines.append(
+            &mut self
+                .columns
+                .iter()
+                .filter_map(|column| column.sql_key())
+                .collect::<Vec<String>>(),
+        );
+
+        // 外键
+        lines.append(
+            &mut self
+                .columns
+                .iter()
+                .filter_map(|column| {
+                    column.sql_references().map(|(table, name)| {
+                        format!(
+                            "FOREIGN KEY (`{}`) REFERENCES `{}` (`{}`)",
+                            column.sql_name(),
+                            table,
+                            name
+                        )
+                    })
+                })
+                .collect::<Vec<String>>(),
+        );
+
+        // 约束
+        if let Some(check) = self.opt.get("check") {
+            lines.push(format!("CHECK ({})", check));
+        }
+
+        format!("CREATE TABLE `{}` (\n    {}\n);", self.name, lines.join(",\n    "))
+    }
+
+    /// 创建 history 表的 sql, 不含 AUTO_INCREMENT 及原有的键
+    fn make_create_history_table(&self) -> String {
+        let mut lines = self
+            .columns
+            .iter()
+            .map(|column| format!("`{}` {} NOT NULL", column.sql_name(), column.sql_type))
+            .collect::<Vec<String>>();
+        lines.push("`op` varchar(8) NOT NULL".to_string());
+        lines.push("`changed_at` datetime(6) NOT NULL".to_string());
+        lines.push("`changed_by` varchar(64) NOT NULL".to_string());
+        lines.push(format!("KEY (`{}`)", self.id_name()));
+
+        format!(
+            "CREATE TABLE `{}_history` (\n    {}\n);",
+            self.name,
+            lines.join(",\n    ")
+        )
+    }
+
+    // C-有逗号结尾, Q-有双引号, B-有反引号, I-去掉 id, P-作为参数, E-赋值, V-Value, EE-相等, F-函数参数
+
+    /// a, b, c,
+    fn make_fields_c(columns: &[&Column]) -> TokenStream2 {
+        TokenStream2::from_iter(columns.iter().map(|column| {
+            let ident = &column.ident;
+            quote!(#ident, )
+        }))
+    }
+
+    /// self.a==other.a && self.b==other.b
+    fn make_fields_ee(&self, use_id: bool) -> TokenStream2 {
+        let code = TokenStream2::from_iter(
+            self.columns
+                .iter()
+                .filter(|column| use_id || column.ident != "id")
+                .filter_map(|column| column.eq_expr())
+                .enumerate()
+                .flat_map(|(i, x)| if i == 0 { vec![x] } else { vec![quote!(&&), x] }),
+        );
+
+        if code.is_empty() {
+            quote!(true)
+        } else {
+            code
+        }
+    }
+
+    /// if !(self.a==other.a) { diff.push(("a", ..)) }
+    fn make_diff(&self) -> TokenStream2 {
+        TokenStream2::from_iter(self.columns.iter().filter_map(|column| {
+            let ident = &column.ident;
+            let real = column.sql_name();
+            column.eq_expr().map(|eq| {
+                quote!(
+                    if !(#eq) {
+                        diff.push((#real, format!("{:?}", self.#ident), format!("{:?}", other.#ident)));
+                    }
+                )
+            })
+        }))
+    }
+
+    /// a:A, b:B, c:C
+    fn make_fields_fi(&self) -> TokenStream2 {
+        map_columns_and_join(
+            &self.columns,
+            |column| column.ident != "id" && !column.is_generated(),
+            |column| {
+                let ident = &column.ident;
+                let ty = &column.field.ty;
+                quote!(#ident: #ty)
+            },
+            quote!(,),
+        )
+    }
+
+    /// let a = match{}; let b = match{};
+    ///
+    /// 缺省按列名查找, #[table(from_row="index")] 时按字段顺序取值, 出错时返回列名
+    fn make_fields_from_row(&self, columns: &[&Column]) -> TokenStream2 {
+        let by_index = self.opt.get("from_row").map(|x| x == "index").unwrap_or(false);
+
+        // 先取出全部中间值, 出错时放回已取出的值, 返回的 row 保持原样
+        let irs = columns
+            .iter()
+            .enumerate()
+            .map(|(i, _)| (format_ident!("__i_{}", i), format_ident!("__ir_{}", i)))
+            .collect::<Vec<_>>();
+        let take = TokenStream2::from_iter(columns.iter().enumerate().map(|(i, column)| {
+            let ty = &column.field.ty;
+            let real = column.sql_name();
+            let (index_var, ir_var) = &irs[i];
+            let rollback = TokenStream2::from_iter(irs[..i].iter().map(|(index_var, ir_var)| {
+                quote!(row.place(#index_var, mysql::prelude::ConvIr::rollback(#ir_var));)
+            }));
+            let index = if by_index {
+                quote!(Some(#i))
+            } else {
+                quote!(row.columns_ref().iter().position(|column| column.name_str() == #real))
+            };
+
+            quote!(
+                let (#index_var, #ir_var) = match #index {
+                    Some(i) => match row.take(i) {
+                        Some(value) => match <#ty as mysql::prelude::FromValue>::get_intermediate(value) {
+                            Ok(ir) => (i, ir),
+                            Err(mysql::FromValueError(value)) => {
+                                row.place(i, value);
+                                #rollback
+                                return Err((#real, row));
+                            }
+                        },
+                        None => {
+                            #rollback
+                            return Err((#real, row));
+                        }
+                    },
+                    None => {
+                        #rollback
+                        return Err((#real, row));
+                    }
+                };
+            )
+        }));
+
+        // 全部成功后转换, 设置同名变量
+        let commit = TokenStream2::from_iter(columns.iter().zip(&irs).map(|(column, (_, ir_var))| {
+            let ident = &column.ident;
+            quote!(let #ident = mysql::prelude::ConvIr::commit(#ir_var);)
+        }));
+
+        quote!(
+            #take
+            #commit
+        )
+    }
+
+    /// 投影的 struct 名及字段, 按 rust 字段名或 sql 名查找字段
+    fn projection(&self, name: &str, columns: &str) -> Result<(Ident, Vec<&Column>), String> {
+        if name.is_empty() {
+            return Err("projection 缺少 name".to_string());
+        }
+        let ident = syn::parse_str::<Ident>(name).map_err(|_| format!("projection 名 {:?} 应为标识符", name))?;
+        let columns = columns
+            .split(',')
+            .map(|x| x.trim())
+            .filter(|x| !x.is_empty())
+            .map(|x| {
+                self.columns
+                    .iter()
+                    .find(|column| column.ident == x || column.sql_name() == x)
+                    .ok_or_else(|| format!("projection {} 中的字段 {} 不存在", name, x))
+            })
+            .collect::<Result<Vec<&Column>, String>>()?;
+        if columns.is_empty() {
+            return Err(format!("projection {} 缺少 columns", name));
+        }
+        Ok((ident, columns))
+    }
+
+    /// 投影 struct, 仅包含部分字段, 有各自的字段列表及 FromRow
+    fn make_projections(&self, vis: &Visibility) -> TokenStream2 {
+        // 出错的投影已由 check_options 报告
+        TokenStream2::from_iter(self.projections.iter().filter_map(|(name, columns, _)| {
+            let (projection_ident, columns) = self.projection(name, columns).ok()?;
+            let fields = TokenStream2::from_iter(columns.iter().map(|column| {
+                let field_vis = &column.field.vis;
+                let ident = &column.ident;
+                let ty = &column.field.ty;
+                quote!(#field_vis #ident: #ty,)
+            }));
+            let fields_b = columns
+                .iter()
+                .map(|column| format!("`{}`", column.sql_name()))
+                .collect::<Vec<String>>()
+                .join(", ");
+            let fields_c = Self::make_fields_c(&columns);
+            let generics = self.projection_generics(&columns);
+            let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+            let from_row = self.make_fields_from_row(&columns);
+            let table_name = &self.name;
+
+            Some(quote!(
+                #[derive(Clone, Debug)]
+                #vis struct #projection_ident #impl_generics #where_clause {
+                    #fields
+                }
+
+                impl #impl_generics #projection_ident #ty_generics #where_clause {
+                    /// `a`, `b`, `c`
+                    pub fn make_fields_b() -> &'static str {
+                        #fields_b
+                    }
+
+                    pub fn table_name() -> &'static str {
+                        #table_name
+                    }
+
+                    /// 从 row 解码, 失败时返回出错的列名及原 row
+                    pub fn try_from_row(mut row: mysql::Row) -> Result<Self, (&'static str, mysql::Row)> {
+                        #from_row
+                        Ok(Self {
+                            #fields_c
+                        })
+                    }
+                }
+
+                impl #impl_generics mysql::prelude::FromRow for #projection_ident #ty_generics #where_clause {
+                    fn from_row(row: mysql::Row) -> Self {
+                        match Self::try_from_row(row) {
+                            Ok(x) => x,
+                            Err((column, row)) => panic!("{} 解码列 {} 失败: {:?}", stringify!(#projection_ident), column, row),
+                        }
+                    }
+
+                    fn from_row_opt(row: mysql::Row) -> Result<Self, mysql::FromRowError> {
+                        Self::try_from_row(row).map_err(|(_, row)| mysql::FromRowError(row))
+                    }
+                }
+            ))
+        }))
+    }
+
+    /// id 字段在 sql 中的名字
+    fn id_name(&self) -> String {
+        self.columns
+            .iter()
+            .find(|column| column.ident == "id")
+            .map(|column| column.sql_name().to_string())
+            .unwrap_or_else(|| "id".to_string())
+    }
+
+    /// id 字段作为命名参数的名字
+    fn id_param(&self) -> String {
+        self.columns
+            .iter()
+            .find(|column| column.ident == "id")
+            .map(|column| column.param_name())
+            .unwrap_or_else(|| "id".to_string())
+    }
+
+    /// a: Default::default(), 用于 #[pc(skip)] 的字段
+    fn make_skipped(&self) -> TokenStream2 {
+        TokenStream2::from_iter(self.skipped.iter().map(|ident| quote!(#ident: Default::default(),)))
+    }
+
+    /// id 字段的类型
+    fn id_ty(&self) -> TokenStream2 {
+        match self.columns.iter().find(|column| column.ident == "id") {
+            Some(column) => column.field.ty.to_token_stream(),
+            None => quote!(u64),
+        }
+    }
+
+    /// table 名模板中的 {xxx}
+    fn template_keys(&self) -> Vec<String> {
+        self.name
+            .split('{')
+            .skip(1)
+            .filter_map(|x| x.split_once('}').map(|(key, _)| key.to_string()))
+            .collect()
+    }
+
+    /// 模板 key 对应的字段, 日期模板取 shard_by 字段及数字位数
+    fn template_column(&self, key: &str) -> Result<(&Column, Option<usize>), String> {
+        let len = match key {
+            "yyyy" => Some(4usize),
+            "yyyymm" => Some(6usize),
+            "yyyymmdd" => Some(8usize),
+            _ => None,
+        };
+        let field = match len {
+            Some(_) => self
+                .opt
+                .get("shard_by")
+                .ok_or_else(|| format!("table 名含日期模板 {{{}}}, 需指定 shard_by", key))?,
+            None => key,
+        };
+        match self.columns.iter().find(|column| column.ident == field) {
+            Some(column) => Ok((column, len)),
+            None if len.is_some() => Err(format!("shard_by 字段 {} 不存在", field)),
+            None => Err(format!("table 名模板中的字段 {} 不存在", field)),
+        }
+    }
+
+    /// 解析 table 名模板, 生成 table_name_for 的函数体
+    fn make_table_name_for(&self) -> TokenStream2 {
+        let keys = self.template_keys();
+        if keys.is_empty() {
+            return quote!(Self::table_name_with(&[]));
+        }
+
+        // 出错的 key 已由 check_options 报告
+        let vars = TokenStream2::from_iter(keys.iter().filter_map(|key| {
+            let (column, len) = self.template_column(key).ok()?;
+            let ident = &column.ident;
+            Some(match len {
+                // 取 shard_by 字段中的数字, 如 2026-10-19 => 202610
+                Some(len) => quote!(
+                        (#key, self.#ident
+                            .to_string()
+                            .chars()
+                            .filter(|c| c.is_ascii_digit())
+                            .take(#len)
+                            .collect::<String>()),
+                ),
+                None => quote!((#key, self.#ident.to_string()),),
+            })
+        }));
+
+        quote!(
+            let vars: Vec<(&str, String)> = vec![#vars];
+            Self::table_name_with(&vars.iter().map(|(k, v)| (*k, v.as_str())).collect::<Vec<(&str, &str)>>())
+        )
+    }
+
+    /// a: 随机值, b: 随机值, 按 rust 类型及 sql 类型生成
+    fn make_fake(&self) -> TokenStream2 {
+        TokenStream2::from_iter(self.columns.iter().map(|column| {
+            let ident = &column.ident;
+            let ty = &column.field.ty;
+            let min = column.opt.get("min").and_then(|x| x.parse::<f64>().ok());
+            let max = column.opt.get("max").and_then(|x| x.parse::<f64>().ok());
+
+            let value = match ty.to_token_stream().to_string().as_str() {
+                _ if column.ident == "id" => quote!(Default::default()),
+                _ if column.opt.contains_key("fake") => match syn::parse_str::<syn::Path>(&column.opt["fake"]) {
+                    Ok(path) => quote!(#path(rng)),
+                    Err(_) => quote!(Default::default()), // check_options 已报错
+                },
+                "i32" | "u32" | "i64" | "u64" => {
+                    let min = min.unwrap_or(0.0).ceil() as i64;
+                    let max = max.unwrap_or(min as f64 + 999.0).floor() as i64;
+                    let span = (max - min + 1).max(1) as u64;
+                    quote!((#min + (rng() % #span) as i64) as #ty)
+                }
+                "f32" | "f64" => {
+                    let min = min.unwrap_or(0.0);
+                    let max = max.unwrap_or(min + 100.0);
+                    quote!((#min + (rng() % 1000001) as f64 / 1000000.0 * (#max - #min)) as #ty)
+                }
+                "bool" => quote!(rng() % 2 == 0),
+                "String" => {
+                    // varchar(n) 限制长度
+                    let limit = column
+                        .sql_type
+                        .strip_prefix("varchar(")
+                        .and_then(|x| x.strip_suffix(')'))
+                        .and_then(|x| x.parse::<u64>().ok())
+                        .unwrap_or(16)
+                        .clamp(1, 16);
+                    quote!({
+                        let len = 1 + rng() % #limit;
+                        (0..len).map(|_| (b'a' + (rng() % 26) as u8) as char).collect::<String>()
+                    })
+                }
+                _ => quote!(Default::default()),
+            };
+
+            quote!(#ident: #value,)
+        }))
+    }
+
+    /// csv 导入导出, 列的顺序及名字同 make_fields_q
+    fn make_csv(&self) -> TokenStream2 {
+        let header = self
+            .columns
+            .iter()
+            .map(|column| column.sql_name())
+            .collect::<Vec<&str>>();
+
+        let to_values = map_columns_and_join(
+            &self.columns,
+            |_| true,
+            |column| {
+                let ident = &column.ident;
+                match option_inner(&column.field.ty) {
+                    Some(_) => quote!(match self.#ident {
+                        Some(ref value) => value.to_string(),
+                        None => String::new(),
+                    }),
+                    None => quote!(self.#ident.to_string()),
+                }
+            },
+            quote!(,),
+        );
+
+        let from_values = TokenStream2::from_iter(self.columns.iter().enumerate().map(|(i, column)| {
+            let ident = &column.ident;
+            let real = column.sql_name();
+            let (ty, optional) = match option_inner(&column.field.ty) {
+                Some(inner) => (inner, true),
+                None => (&column.field.ty, false),
+            };
+
+            // 日期时间兼容 2026/10/19 及 2026-10-19T10:00:00
+            let text = match ty.to_token_stream().to_string().as_str() {
+                "SqlDate" => quote!(text.trim().replace('/', "-")),
+                "SqlTime" => quote!(text.trim().replace('/', "-").replacen('T', " ", 1)),
+                _ => quote!(text.to_string()),
+            };
+            let parse = quote!(
+                #text
+                    .parse::<#ty>()
+                    .map_err(|err| format!("{}: {}", #real, err))?
+            );
+
+            let value = if optional {
+                quote!(if text.is_empty() { None } else { Some(#parse) })
+            } else {
+                parse
+            };
+
+            quote!(
+                let #ident = {
+                    let text = record.get(#i).ok_or_else(|| format!("{}: 缺少", #real))?;
+                    #value
+                };
+            )
+        }));
+        let fields_c = Self::make_fields_c(&self.columns.iter().collect::<Vec<&Column>>());
+        let skipped = self.make_skipped();
+
+        quote!(
+            /// csv 表头, 同 make_fields_q
+            pub fn csv_header() -> csv::StringRecord {
+                csv::StringRecord::from(vec![#(#header),*])
+            }
+
+            /// 转为 csv 记录, 可用于 csv 或 tsv (delimiter 为 \t) 的 Writer
+            pub fn to_csv_record(&self) -> csv::StringRecord {
+                csv::StringRecord::from(vec![#to_values])
+            }
+
+            /// 从 csv 记录解析, 列的顺序同 csv_header, 失败时返回列名及原因
+            pub fn from_csv_record(record: &csv::StringRecord) -> Result<Self, String> {
+                #from_values
+                Ok(Self {
+                    #fields_c
+                    #skipped
+                })
+            }
+        )
+    }
+
+    /// 调用 table 属性指定的钩子函数, 未指定时为空
+    fn make_hook(&self, key: &str, args: TokenStream2) -> TokenStream2 {
+        // 无效的路径已由 check_options 报告
+        match self.opt.get(key).map(|x| syn::parse_str::<syn::Path>(x)) {
+            Some(Ok(path)) => quote!(#path(#args);),
+            _ => quote!(),
+        }
+    }
+
+    /// 校验, 字段的 validate/min/max/regex/not_empty 及 table 的 validate
+    fn make_validate(&self, krate: &syn::Path) -> TokenStream2 {
+        let checks = TokenStream2::from_iter(self.columns.iter().map(|column| {
+            let ident = &column.ident;
+            let real = column.sql_name();
+            let mut code = TokenStream2::new();
+
+            if let Some(Ok(path)) = column.opt.get("validate").map(|x| syn::parse_str::<syn::Path>(x)) {
+                code.extend(quote!(
+                    if let Err(message) = #path(&self.#ident) {
+                        errors.push(#krate::use_sql::ValidationError {
+                            column: Some(#real),
+                            message,
+                        });
+                    }
+                ));
+            }
+            if let Some(Ok(min)) = column.opt.get("min").map(|x| x.parse::<f64>()) {
+                code.extend(quote!(
+                    if (self.#ident as f64) < #min {
+                        errors.push(#krate::use_sql::ValidationError {
+                            column: Some(#real),
+                            message: format!("小于 {}", #min),
+                        });
+                    }
+                ));
+            }
+            if let Some(Ok(max)) = column.opt.get("max").map(|x| x.parse::<f64>()) {
+                code.extend(quote!(
+                    if (self.#ident as f64) > #max {
+                        errors.push(#krate::use_sql::ValidationError {
+                            column: Some(#real),
+                            message: format!("大于 {}", #max),
+                        });
+                    }
+                ));
+            }
+            if let Some(regex) = column.opt.get("regex") {
+                code.extend(quote!(
+                    static REGEX: std::sync::OnceLock<Result<regex::Regex, regex::Error>> = std::sync::OnceLock::new();
+                    match REGEX.get_or_init(|| regex::Regex::new(#regex)) {
+                        Ok(regex) if regex.is_match(&self.#ident) => {}
+                        Ok(_) => errors.push(#krate::use_sql::ValidationError {
+                            column: Some(#real),
+                            message: format!("不匹配 {}", #regex),
+                        }),
+                        Err(err) => errors.push(#krate::use_sql::ValidationError {
+                            column: Some(#real),
+                            message: format!("regex {} 无效: {}", #regex, err),
+                        }),
+                    }
+                ));
+            }
+            if column.opt.contains_key("not_empty") {
+                code.extend(quote!(
+                    if self.#ident.is_empty() {
+                        errors.push(#krate::use_sql::ValidationError {
+                            column: Some(#real),
+                            message: "为空".to_string(),
+                        });
+                    }
+                ));
+            }
+
+            // 每个字段单独一个块, 避免 REGEX 重名
+            if code.is_empty() {
+                code
+            } else {
+                quote!({ #code })
+            }
+        }));
+
+        let table_check = match self.opt.get("validate").map(|x| syn::parse_str::<syn::Path>(x)) {
+            Some(Ok(path)) => quote!(
+                if let Err(message) = #path(self) {
+                    errors.push(#krate::use_sql::ValidationError { column: None, message });
+                }
+            ),
+            _ => quote!(),
+        };
+
+        quote!(
+            #[allow(unused_mut)]
+            let mut errors: Vec<#krate::use_sql::ValidationError> = Vec::new();
+            #checks
+            #table_check
+            errors
+        )
+    }
+
+    /// 是否有字段或 table 的校验规则
+    fn has_validation(&self) -> bool {
+        self.opt.contains_key("validate")
+            || self.columns.iter().any(|column| {
+                ["validate", "min", "max", "regex", "not_empty"]
+                    .iter()
+                    .any(|key| column.opt.contains_key(*key))
+            })
+    }
+
+    /// left name right sep ... left name right
+    fn fields_string(&self, left: &str, right: &str, sep: &str, use_id: bool) -> String {
+        self.columns
+            .iter()
+            .filter(if use_id {
+                |_: &&Column| true
+            } else {
+                |column: &&Column| column.ident != "id"
+            })
+            .map(|column| format!("{}{}{}", left, column.sql_name(), right))
+            .collect::<Vec<String>>()
+            .join(sep)
+    }
+
+    /// 同 fields_string, 用于写入, 不含生成列
+    fn fields_string_w(&self, left: &str, right: &str, sep: &str, use_id: bool) -> String {
+        self.columns
+            .iter()
+            .filter(|column| (use_id || column.ident != "id") && !column.is_generated())
+            .map(|column| format!("{}{}{}", left, column.sql_name(), right))
+            .collect::<Vec<String>>()
+            .join(sep)
+    }
+
+    /// :a, :b, :c, 用于写入, 不含生成列
+    fn fields_param(&self, use_id: bool) -> String {
+        self.columns
+            .iter()
+            .filter(|column| (use_id || column.ident != "id") && !column.is_generated())
+            .map(|column| format!(":{}", column.param_name()))
+            .collect::<Vec<String>>()
+            .join(", ")
+    }
+
+    /// left name mid param right sep ... left name mid param right, 用于写入, 不含生成列
+    fn fields_string2(&self, left: &str, mid: &str, right: &str, sep: &str, use_id: bool) -> String {
+        self.columns
+            .iter()
+            .filter(|column| (use_id || column.ident != "id") && !column.is_generated())
+            .map(|column| format!("{}{}{}{}{}", left, column.sql_name(), mid, column.param_name(), right))
+            .collect::<Vec<String>>()
+            .join(sep)
+    }
+
+    /// make_create_table 及 make_fields_* 的 sql 字符串, view 不可写入
+    fn sql_strings(&self) -> SqlStrings {
+        let is_view = self.opt.contains_key("view");
+        let write = |code: String| if is_view { String::new() } else { code };
+        SqlStrings {
+            create_table: self.make_create_table(),
+            fields_b: self.fields_string("`", "`", ", ", true), // `a`, `b`, `c`
+            fields_bi: write(self.fields_string_w("`", "`", ", ", false)), // `b`, `c`
+            fields_e: self.fields_string2("`", "`=:", "", ", ", true), // `a`=:a, `b`=:b
+            fields_ei: write(self.fields_string2("`", "`=:", "", ", ", false)), // `b`=:b, `c`=:c
+            fields_p: self.fields_param(true),                  // :a, :b, :c
+            fields_pi: write(self.fields_param(false)),         // :b, :c
+            fields_q: self.fields_string("\"", "\"", ", ", true), // "a", "b", "c"
+            fields_qc: self.fields_string("\"", "\", ", "", true), // "a", "b", "c",
+        }
+    }
+
+    /// vec![("a", self.a), ("b", self.b)]
+    fn make_fields_v(&self, use_id: bool) -> TokenStream2 {
+        let code = map_columns_and_join(
+            &self.columns,
+            |column| (use_id || column.ident != "id") && !column.is_generated(),
+            |column| {
+                let param = column.param_name();
+                let ident = &column.ident;
+                quote!((#param, self.#ident.clone().into()))
+            },
+            quote!(,),
+        );
+        quote!(
+            let v: Vec<(&str, mysql::Value)> = vec![ #code ];
+            mysql::params::Params::from(v)
+        )
+    }
+
+    /// 构造
+    fn new() -> Self {
+        Self {
+            name: String::new(),
+            name_span: Span::call_site(),
+            spans: HashMap::new(),
+            who: "WhoCreateDbPool".to_string(),
+            opt: HashMap::new(),
+            projections: Vec::new(),
+            generics: Generics::default(),
+            columns: Vec::new(),
+            skipped: Vec::new(),
+        }
+    }
+
+    /// 解析 struct derive 属性
+    fn parse_struct_derive(&mut self, dvi: &DeriveInput) {
+        self.name_span = dvi.ident.span();
+
+        // 遍历每个 #[table()], 更新 name
+        for attr in dvi.attrs.iter() {
+            if let Ok(Meta::List(MetaList {
+                ref path, ref nested, ..
+            })) = attr.parse_meta()
+            {
+                if path.get_ident().unwrap() == "table" {
+                    self.extract_table_meta(&nested.iter().collect());
+                }
+            }
+        }
+    }
+
+    /// 解析泛型, 为用到泛型参数的字段类型增加约束, 需在 parse_struct_fields 之后调用
+    fn parse_struct_generics(&mut self, generics: &Generics) {
+        let names = generic_param_names(generics);
+        let mut generics = generics.clone();
+
+        for column in &self.columns {
+            let ty = &column.field.ty;
+            if uses_generics(ty, &names) {
+                generics.make_where_clause().predicates.push(parse_quote!(
+                    #ty: Clone
+                        + PartialEq
+                        + std::fmt::Debug
+                        + Into<mysql::Value>
+                        + mysql::prelude::FromValue
+                ));
+            }
+        }
+
+        self.generics = generics;
+    }
+
+    /// 投影用到的泛型, 仅保留 columns 中用到的参数及相关约束
+    fn projection_generics(&self, columns: &[&Column]) -> Generics {
+        let unused = generic_param_names(&self.generics)
+            .into_iter()
+            .filter(|name| {
+                !columns
+                    .iter()
+                    .any(|column| uses_generics(&column.field.ty, std::slice::from_ref(name)))
+            })
+            .collect::<Vec<String>>();
+
+        let mut generics = self.generics.clone();
+        generics.params = generics
+            .params
+            .into_iter()
+            .filter(|param| !unused.contains(&generic_param_name(param)))
+            .collect();
+        if let Some(where_clause) = generics.where_clause.as_mut() {
+            where_clause.predicates = where_clause
+                .predicates
+                .clone()
+                .into_iter()
+                .filter(|predicate| !uses_generics(predicate, &unused))
+                .collect();
+        }
+
+        generics
+    }
+
+    /// 解析 struct fields 属性
+    fn parse_struct_fields(&mut self, fields: &Vec<Field>) -> Result<(), syn::Error> {
+        let rename_all = self.opt.get("rename_all").cloned();
+        let prefix = self.opt.get("prefix").cloned().unwrap_or_default();
+        if let Some(rule) = &rename_all {
+            if !RENAME_RULES.contains(&rule.as_str()) {
+                return Err(syn::Error::new(
+                    self.option_span("rename_all"),
+                    format!("rename_all 应为 {}, 而不是 {:?}", RENAME_RULES.join(", "), rule),
+                ));
+            }
+        }
+
+        // 遍历每个 field
+        for field in fields {
+            // #[pc(skip)] 的字段不在 table 中, 构造时取 Default
+            if is_skipped(field)? {
+                self.skipped.extend(field.ident.clone());
+                continue;
+            }
+
+            let mut sql_opt = HashMap::new();
+            let mut spans = HashMap::new();
+
+            // 遍历每个 #[column()], 更新 sql_type, opt
+            for attr in &field.attrs {
+                if !attr.path.to_token_stream().to_string().contains("column") {
+                    continue;
+                }
+
+                if let Ok(Meta::List(MetaList { ref nested, .. })) = attr.parse_meta() {
+                    // 解析并处理 opt
+                    sql_opt.extend(Self::extract_column_meta(&nested.iter().collect()));
+
+                    // 记录选项的位置
+                    for meta_item in nested.iter() {
+                        if let NestedMeta::Meta(Meta::NameValue(MetaNameValue { ref path, ref lit, .. })) = *meta_item {
+                            if let Some(ident) = path.get_ident() {
+                                spans.insert(ident.to_string(), lit.span());
+                            }
+                        }
+                    }
+                }
+            }
+
+            // 记录, rename_all 及 prefix 仅作用于未指定 name 的字段
+            let mut column = Column::new(field.clone(), sql_opt, spans);
+            if let Some(rule) = &rename_all {
+                column.name = rename_column(&column.name, rule);
+            }
+            column.name = format!("{}{}", prefix, column.name);
+            self.columns.push(column);
+        }
+        Ok(())
+    }
+}
+
+/// rust 类型 -> AsSqlModel 类型, 未命中的不变
+const TYPE_MAP1: [(&str, &str); 9] = [
+    ("i32", "int"),
+    ("u32", "int"),
+    ("i64", "bigint"),
+    ("u64", "bigint"),
+    ("f32", "double"),
+    ("f64", "double"),
+    ("String", "str"),
+    ("SqlDate", "date"),
+    ("SqlTime", "datetime"),
+];
+
+/// AsSqlModel 类型 -> sql 类型, 未命中的不变
+const TYPE_MAP2: [(&str, &str); 9] = [
+    ("str", "varchar(32)"),
+    ("text", "text(65535)"),
+    ("longtext", "longtext"),
+    ("bool", "bool"),
+    ("int", "int(11)"),
+    ("bigint", "int(20)"),
+    ("double", "double"),
+    ("date", "date"),
+    ("datetime", "datetime(6)"),
+];
+
+/// mysql 保留字, 常见部分
+const RESERVED_WORDS: [&str; 72] = [
+    "ADD",
+    "ALL",
+    "ALTER",
+    "AND",
+    "AS",
+    "ASC",
+    "BETWEEN",
+    "BY",
+    "CASE",
+    "CHECK",
+    "COLUMN",
+    "CONSTRAINT",
+    "CREATE",
+    "CROSS",
+    "DATABASE",
+    "DEFAULT",
+    "DELETE",
+    "DESC",
+    "DESCRIBE",
+    "DISTINCT",
+    "DROP",
+    "ELSE",
+    "EXISTS",
+    "FALSE",
+    "FOR",
+    "FOREIGN",
+    "FROM",
+    "FULLTEXT",
+    "GROUP",
+    "HAVING",
+    "IF",
+    "IN",
+    "INDEX",
+    "INNER",
+    "INSERT",
+    "INTERVAL",
+    "INTO",
+    "IS",
+    "JOIN",
+    "KEY",
+    "KEYS",
+    "LEFT",
+    "LIKE",
+    "LIMIT",
+    "LOCK",
+    "MATCH",
+    "NOT",
+    "NULL",
+    "ON",
+    "OR",
+    "ORDER",
+    "OUTER",
+    "PRIMARY",
+    "RANGE",
+    "REFERENCES",
+    "REPLACE",
+    "RIGHT",
+    "ROW",
+    "ROWS",
+    "SELECT",
+    "SET",
+    "TABLE",
+    "THEN",
+    "TO",
+    "TRUE",
+    "UNION",
+    "UNIQUE",
+    "UPDATE",
+    "USING",
+    "VALUES",
+    "WHEN",
+    "WHERE",
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(input: &str) -> DeriveInput {
+        syn::parse_str(input).expect("测试输入应为 struct")
+    }
+
+    fn strings(input: &str) -> SqlStrings {
+        sql_strings(&parse(input)).expect("名字应合法")
+    }
+
+    const USER: &str = r#"
+        #[table(name = "user")]
+        struct User {
+            #[column(auto, key = "PRIMARY")]
+            id: u64,
+            #[column(name = "user_name", key = "UNIQUE")]
+            name: String,
+            #[column(key)]
+            age: i32,
+        }
+    "#;
+
+    #[test]
+    fn user_sql() {
+        insta::assert_debug_snapshot!(strings(USER));
+    }
+
+    /// 展开结果中各 impl 的 (trait 名, 方法名)
+    fn impl_methods(input: &str) -> Vec<(String, Vec<String>)> {
+        let file: syn::File = syn::parse2(expand(&parse(input))).unwrap();
+        let mut impls = Vec::new();
+        for item in file.items {
+            let block = match item {
+                syn::Item::Const(syn::ItemConst { expr, .. }) => match *expr {
+                    syn::Expr::Block(block) => block.block,
+                    _ => continue,
+                },
+                _ => continue,
+            };
+            for stmt in block.stmts {
+                if let syn::Stmt::Item(syn::Item::Impl(item_impl)) = stmt {
+                    let trait_name = item_impl
+                        .trait_
+                        .as_ref()
+                        .map(|(_, path, _)| path.segments.last().unwrap().ident.to_string())
+                        .unwrap_or_default();
+                    let methods = item_impl
+                        .items
+                        .iter()
+                        .filter_map(|x| match x {
+                            syn::ImplItem::Method(method) => Some(method.sig.ident.to_string()),
+                            _ => None,
+                        })
+                        .collect();
+                    impls.push((trait_name, methods));
+                }
+            }
+        }
+        impls
+    }
+
+    #[test]
+    fn user_expand() {
+        let impls = impl_methods(USER);
+        let methods = |trait_name: &str| {
+            impls
+                .iter()
+                .filter(|(name, _)| name == trait_name)
+                .flat_map(|(_, methods)| methods.clone())
+                .collect::<Vec<_>>()
+        };
+
+        // 写操作及分表相关方法
+        let own = methods("");
+        for name in [
+            "create_with",
+            "create_tx",
+            "update_tx",
+            "delete_tx",
+            "table_name_with",
+            "make_create_table_for",
+        ] {
+            assert!(own.contains(&name.to_string()), "缺少 {}", name);
+        }
+        // 未开启的 flag 及无校验规则时不生成
+        for name in [
+            "validate",
+            "validation_errors",
+            "validate_joined",
+            "fake",
+            "page",
+            "scan_by_id",
+            "diff",
+            "verify_schema",
+            "load_data_sql",
+        ] {
+            assert!(!own.contains(&name.to_string()), "多出 {}", name);
+        }
+        // 无校验规则时不依赖 python_comm 的 ValidationError
+        assert!(!expand(&parse(USER)).to_string().contains("ValidationError"));
+
+        // setter 与字段同名
+        assert!(own.contains(&"name".to_string()) && own.contains(&"age".to_string()));
+
+        // 无钩子/校验/reader/audit 时使用 python_comm 缺省的 create/update/delete, 不依赖 DbPool::get_conn
+        let model = methods("SqlModel");
+        for name in ["lock", "table_name", "make_create_table"] {
+            assert!(model.contains(&name.to_string()), "SqlModel 缺少 {}", name);
+        }
+        for name in ["create", "update", "delete"] {
+            assert!(!model.contains(&name.to_string()), "SqlModel 多出 {}", name);
+        }
+        assert!(!expand(&parse(USER)).to_string().contains("get_conn"));
+        assert_eq!(
+            methods("FromRow"),
+            vec!["from_row".to_string(), "from_row_opt".to_string()]
+        );
+
+        // 开启 flag 后生成对应方法
+        let flagged = USER.replace(
+            r#"#[table(name = "user")]"#,
+            r#"#[table(name = "user", fake, page, diff)]"#,
+        );
+        let own = impl_methods(&flagged)
+            .into_iter()
+            .filter(|(name, _)| name.is_empty())
+            .flat_map(|(_, methods)| methods)
+            .collect::<Vec<_>>();
+        for name in ["fake", "fake_with", "page", "page_in", "diff"] {
+            assert!(own.contains(&name.to_string()), "缺少 {}", name);
+        }
+
+        // 钩子只覆盖相关的写操作, audit 覆盖 update/delete
+        for (opt, expected) in [
+            (r#"before_insert = "hook""#, vec!["create"]),
+            (r#"before_update = "hook""#, vec!["update"]),
+            ("audit", vec!["update", "delete"]),
+            (r#"reader = "Replica""#, vec!["create", "update", "delete"]),
+        ] {
+            let source = USER.replace(
+                r#"#[table(name = "user")]"#,
+                &format!(r#"#[table(name = "user", {})]"#, opt),
+            );
+            let model = impl_methods(&source)
+                .into_iter()
+                .filter(|(name, _)| name == "SqlModel")
+                .flat_map(|(_, methods)| methods)
+                .filter(|name| ["create", "update", "delete"].contains(&name.as_str()))
+                .collect::<Vec<_>>();
+            assert_eq!(model, expected, "{}", opt);
+        }
+    }
+
+    #[test]
+    fn generated_and_check_sql() {
+        insta::assert_debug_snapshot!(strings(
+            r#"
+            #[table(name = "item", check = "qty >= 0")]
+            struct Item {
+                id: u64,
+                price: f64,
+                qty: i64,
+                #[column(generated = "price * qty", stored)]
+                total: f64,
+                #[column(generated = "price > 100")]
+                expensive: bool,
+            }
+            "#
+        ));
+    }
+
+    #[test]
+    fn references_sql() {
+        insta::assert_snapshot!(
+            strings(
+                r#"
+            #[table(name = "order_line")]
+            struct OrderLine {
+                id: u64,
+                #[column(references = "orders(id)")]
+                order_id: u64,
+                #[column(references = "item")]
+                item_id: u64,
+            }
+            "#
+            )
+            .create_table
+        );
+        assert!(sql_strings(&parse(
+            "#[table(name = \"a\")] struct A { id: u64, #[column(references = \"order(id)\")] b: u64 }"
+        ))
+        .is_err());
+    }
+
+    #[test]
+    fn schema_of_table() {
+        let table = table_schema(&parse(
+            r#"
+            #[table(name = "log_{yyyymm}", shard_by = "created_at", audit)]
+            struct Log {
+                #[column(auto, key = "PRIMARY")]
+                id: u64,
+                #[column(name = "user", references = "user")]
+                user_id: u64,
+                created_at: String,
+            }
+            "#,
+        ))
+        .expect("名字应合法");
+        assert_eq!(
+            (table.struct_name.as_str(), table.view, table.sharded),
+            ("Log", false, true)
+        );
+        assert!(table.create_history_table.is_some());
+        let columns = table
+            .columns
+            .iter()
+            .map(|x| {
+                (
+                    x.field.as_str(),
+                    x.name.as_str(),
+                    x.key.as_deref(),
+                    x.auto,
+                    x.references.clone(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            columns,
+            vec![
+                ("id", "id", Some("PRIMARY"), true, None),
+                (
+                    "user_id",
+                    "user",
+                    None,
+                    false,
+                    Some(("user".to_string(), "id".to_string()))
+                ),
+                ("created_at", "created_at", None, false, None),
+            ]
+        );
+    }
+
+    #[test]
+    fn registry_expand() {
+        let table = table_schema(&parse(USER)).expect("名字应合法");
+        insta::assert_snapshot!(table.make_registry(&parse_quote!(::python_comm)).to_string());
+    }
+
+    #[test]
+    fn rename_all_sql() {
+        insta::assert_debug_snapshot!(strings(
+            r#"
+            #[table(name = "legacy", rename_all = "camelCase", prefix = "t_")]
+            struct Legacy {
+                id: u64,
+                user_name: String,
+                #[column(name = "AGE")]
+                age: i32,
+            }
+            "#
+        ));
+        insta::assert_debug_snapshot!(strings(
+            r#"
+            #[table(name = "kebab", rename_all = "kebab-case")]
+            struct Kebab {
+                id: u64,
+                user_name: String,
+            }
+            "#
+        ));
+    }
+
+    #[test]
+    fn rename_rules() {
+        assert_eq!(
+            RENAME_RULES.map(|rule| rename_column("user_first_name", rule)),
+            [
+                "user_first_name",
+                "userFirstName",
+                "UserFirstName",
+                "USER_FIRST_NAME",
+                "user-first-name"
+            ]
+        );
+        assert_eq!(
+            RENAME_RULES.map(|rule| rename_column("id", rule)),
+            ["id", "id", "Id", "ID", "id"]
+        );
+
+        // 未知规则及非法 prefix 报错, kebab-case 生成的 - 不报错
+        let err = |input: &str| sql_strings(&parse(input)).unwrap_err().to_string();
+        assert!(
+            err(r#"#[table(name = "a", rename_all = "lowercase")] struct A { id: u64 }"#).contains("rename_all 应为")
+        );
+        assert!(
+            err(r#"#[table(name = "a", rename_all = "kebab-case", prefix = "a-")] struct A { first_name: u64 }"#)
+                .starts_with("prefix \"a-\"")
+        );
+        assert!(err(r#"#[table(name = "a", prefix = "a-")] struct A { id: u64 }"#).starts_with("prefix"));
+        assert!(sql_strings(&parse(
+            r#"#[table(name = "a", rename_all = "kebab-case", prefix = "a_")] struct A { first_name: u64 }"#
+        ))
+        .is_ok());
+    }
+
+    #[test]
+    fn skipped_fields() {
+        let input = parse(
+            r#"
+            #[table(name = "cached")]
+            struct Cached {
+                id: u64,
+                name: String,
+                #[pc(skip)]
+                cache: Vec<u8>,
+                _renames_old: i32,
+            }
+            "#,
+        );
+        let strings = sql_strings(&input).expect("名字应合法");
+        assert_eq!(strings.fields_b, "`id`, `name`");
+        assert!(!strings.create_table.contains("`cache`") && !strings.create_table.contains("_renames_old"));
+        let code = expand(&input).to_string();
+        assert!(code.contains("cache : Default :: default ()"));
+        assert!(code.contains("_renames_old : Default :: default ()"));
+    }
+
+    #[test]
+    fn view_sql() {
+        insta::assert_debug_snapshot!(strings(
+            r#"
+            #[table(name = "user_age", view = "SELECT `id`, `age` FROM `user`")]
+            struct UserAge {
+                id: u64,
+                age: i32,
+            }
+            "#
+        ));
+    }
+
+    #[test]
+    fn audit_expand() {
+        insta::assert_snapshot!(expand(&parse(
+            r#"
+            #[table(name = "price", audit, csv, crate = "crate::pc")]
+            struct Price {
+                id: u64,
+                #[column(eq_epsilon = 1e-9)]
+                value: f64,
+            }
+            "#
+        ))
+        .to_string());
+    }
+
+    #[test]
+    fn fake_regex() {
+        let code = expand(&parse(
+            r#"
+            #[table(name = "account", fake)]
+            struct Account {
+                id: u64,
+                #[column(regex = "^[a-z]+@[a-z]+$")]
+                email: String,
+                #[column(regex = "^[A-Z][0-9]{3}$", fake = "gen::code")]
+                code: String,
+            }
+            "#,
+        ))
+        .to_string();
+        // 未指定 fake 的 regex 字段不参与重试, 指定 fake 的字段调用该函数
+        assert!(code.contains(r#"errors . retain (| error | ! matches ! (error . column , Some (column) if ["email"]"#));
+        assert!(code.contains("code : gen :: code (rng)"));
+
+        // 无 regex 字段时不过滤
+        let code = expand(&parse(
+            r#"#[table(name = "a", fake)] struct A { id: u64, #[column(min = 1)] age: i32 }"#,
+        ))
+        .to_string();
+        assert!(code.contains("validation_errors") && !code.contains("retain"));
+    }
+
+    #[test]
+    fn bad_options() {
+        let err = |input: &str| sql_strings(&parse(input)).unwrap_err().to_string();
+        assert_eq!(
+            err(r#"#[table(name = "a", fake)] struct A { id: u64, #[column(fake = "1x")] a: String }"#),
+            "fake 应为函数路径, 而不是 \"1x\""
+        );
+
+        // table 名模板
+        assert_eq!(
+            err(r#"#[table(name = "log_{yyyymm}")] struct A { id: u64, created_at: String }"#),
+            "table 名含日期模板 {yyyymm}, 需指定 shard_by"
+        );
+        assert_eq!(
+            err(r#"#[table(name = "log_{yyyymm}", shard_by = "created")] struct A { id: u64 }"#),
+            "shard_by 字段 created 不存在"
+        );
+        assert_eq!(
+            err(r#"#[table(name = "log_{tenant}")] struct A { id: u64 }"#),
+            "table 名模板中的字段 tenant 不存在"
+        );
+
+        // 校验规则
+        let column = |attr: &str| {
+            err(&format!(
+                r#"#[table(name = "a")] struct A {{ id: u64, #[column({})] age: i32 }}"#,
+                attr
+            ))
+        };
+        assert_eq!(column(r#"min = "zero""#), "min 应为数字, 而不是 \"zero\"");
+        assert_eq!(column(r#"max = "1a""#), "max 应为数字, 而不是 \"1a\"");
+        assert_eq!(column(r#"eq_epsilon = "tiny""#), "eq_epsilon 应为数字, 而不是 \"tiny\"");
+        assert_eq!(column(r#"validate = "check age""#), "validate 应为函数路径, 而不是 \"check age\"");
+        assert_eq!(
+            err(r#"#[table(name = "a", validate = "1")] struct A { id: u64 }"#),
+            "validate 应为函数路径, 而不是 \"1\""
+        );
+
+        assert_eq!(
+            err(r#"#[table(name = "a", view = "SELECT 1", audit)] struct A { id: u64 }"#),
+            "view 为只读, 不能与 audit 同时使用"
+        );
+        assert_eq!(
+            err(r#"#[table(name = "a", crate = "crate::")] struct A { id: u64 }"#),
+            "crate 应为路径, 而不是 \"crate::\""
+        );
+
+        // DbPool 及 primary_reads_ms
+        assert_eq!(
+            err(r#"#[table(name = "a", reader = "db::Replica", primary_reads_ms = "soon")] struct A { id: u64 }"#),
+            "primary_reads_ms 应为整数毫秒, 而不是 \"soon\""
+        );
+        assert_eq!(
+            err(r#"#[table(name = "a", writer = "db Primary")] struct A { id: u64 }"#),
+            "writer 应为类名或路径, 而不是 \"db Primary\""
+        );
+        let code = expand(&parse(
+            r#"#[table(name = "a", writer = "db::Primary", reader = "crate::db::Replica")] struct A { id: u64 }"#,
+        ))
+        .to_string();
+        assert!(code.contains("db :: Primary :: lock ()") && code.contains("crate :: db :: Replica :: lock ()"));
+
+        // 钩子
+        assert_eq!(
+            err(r#"#[table(name = "a", before_insert = "hooks::")] struct A { id: u64 }"#),
+            "before_insert 应为函数路径, 而不是 \"hooks::\""
+        );
+        assert_eq!(
+            err(r#"#[table(name = "a", after_load = "")] struct A { id: u64 }"#),
+            "after_load 应为函数路径, 而不是 \"\""
+        );
+
+        // projection
+        let projection = |attr: &str| {
+            err(&format!(
+                r#"#[table(name = "a", {})] struct A {{ id: u64, name: String }}"#,
+                attr
+            ))
+        };
+        assert_eq!(projection(r#"projection(columns = "id")"#), "projection 缺少 name");
+        assert_eq!(projection(r#"projection(name = "P")"#), "projection P 缺少 columns");
+        assert_eq!(
+            projection(r#"projection(name = "1P", columns = "id")"#),
+            "projection 名 \"1P\" 应为标识符"
+        );
+        assert_eq!(
+            projection(r#"projection(name = "P", columns = "id, age")"#),
+            "projection P 中的字段 age 不存在"
+        );
+        assert!(table_schema(&parse(
+            r#"#[table(name = "a", projection(name = "P", columns = "age"))] struct A { id: u64 }"#
+        ))
+        .is_err());
+    }
+
+    #[test]
+    fn reserved_names() {
+        assert!(sql_strings(&parse("#[table(name = \"order\")] struct Order { id: u64 }")).is_err());
+        assert!(sql_strings(&parse(
+            "#[table(name = \"a\")] struct A { id: u64, #[column(name = \"select\")] a: i32 }"
+        ))
+        .is_err());
+        assert!(sql_strings(&parse(
+            "#[table(name = \"a\")] struct A { id: u64, #[column(name = \"a-b\")] a: i32 }"
+        ))
+        .is_err());
+        assert!(sql_strings(&parse("#[table(name = \"a\")] struct A { id: u64, a: i32 }")).is_ok());
+        assert!(sql_strings(&parse(
+            "#[table(name = \"order\", allow_reserved)] struct Order { id: u64 }"
+        ))
+        .is_ok());
+    }
+}
@@ -0,0 +1,6 @@
+//!
+//! python_comm_macros 的实现, 不依赖 proc_macro, 供 python_comm_macros 及 schema 工具使用
+//!
+
+pub mod asm;
+pub mod pc;
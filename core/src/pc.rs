@@ -0,0 +1,26 @@
+use syn::{Field, Meta, NestedMeta};
+
+/// #[pc(skip)] 或以 _renames_ 开头 (旧的约定) 的字段, AsSqlModel/QuickAssign/LimitPack 均忽略, #[pc(...)] 有误时报错
+pub fn is_skipped(field: &Field) -> Result<bool, syn::Error> {
+    if let Some(ident) = &field.ident {
+        if ident.to_string().starts_with("_renames_") {
+            return Ok(true);
+        }
+    }
+
+    let mut skip = false;
+    for attr in field.attrs.iter().filter(|attr| attr.path.is_ident("pc")) {
+        match attr.parse_meta() {
+            Ok(Meta::List(list)) => {
+                for nested in list.nested.iter() {
+                    match nested {
+                        NestedMeta::Meta(Meta::Path(path)) if path.is_ident("skip") => skip = true,
+                        _ => return Err(syn::Error::new_spanned(attr, "pc 仅支持 skip")),
+                    }
+                }
+            }
+            _ => return Err(syn::Error::new_spanned(attr, "pc 应为 #[pc(skip)]")),
+        }
+    }
+    Ok(skip)
+}
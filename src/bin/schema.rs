@@ -0,0 +1,406 @@
+//!
+//! 导出 AsSqlModel 的 schema
+//!
+//! schema <src 目录> [输出目录]
+//!
+//! 安装: cargo install python_comm_macros --bin schema
+//!
+//! 扫描 src 目录下全部 .rs 中 #[derive(AsSqlModel)] 的 struct, 按外键依赖排序, 生成 schema.sql 及 schema.json
+//!
+
+use {
+    python_comm_macros_core::asm::{self, TableSchema},
+    std::{
+        collections::BTreeMap,
+        fs,
+        path::{Path, PathBuf},
+        process::exit,
+    },
+    syn::{Attribute, DeriveInput, Item, Meta, NestedMeta},
+};
+
+/// 递归列出目录下的 .rs 文件
+fn list_files(dir: &Path, files: &mut Vec<PathBuf>) -> std::io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            list_files(&path, files)?;
+        } else if path.extension().map(|x| x == "rs").unwrap_or(false) {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// 是否 #[derive(AsSqlModel)]
+fn is_model(attrs: &[Attribute]) -> bool {
+    attrs
+        .iter()
+        .filter(|attr| attr.path.is_ident("derive"))
+        .any(|attr| match attr.parse_meta() {
+            Ok(Meta::List(list)) => list.nested.iter().any(|nested| match nested {
+                NestedMeta::Meta(meta) => meta
+                    .path()
+                    .segments
+                    .last()
+                    .map(|x| x.ident == "AsSqlModel")
+                    .unwrap_or(false),
+                _ => false,
+            }),
+            _ => false,
+        })
+}
+
+/// 收集 items 中的 model, 含内嵌的 mod, 返回错误个数
+fn collect_models(items: &[Item], path: &Path, tables: &mut Vec<TableSchema>) -> usize {
+    let mut errors = 0;
+    for item in items {
+        match item {
+            Item::Struct(item) if is_model(&item.attrs) => match asm::table_schema(&DeriveInput::from(item.clone())) {
+                Ok(table) => tables.push(table),
+                Err(err) => {
+                    eprintln!("{}: {}: {}", path.display(), item.ident, err);
+                    errors += 1;
+                }
+            },
+            Item::Mod(item) => {
+                if let Some((_, items)) = &item.content {
+                    errors += collect_models(items, path, tables);
+                }
+            }
+            _ => {}
+        }
+    }
+    errors
+}
+
+/// 按外键依赖排序, 被引用的表在前, 同一批按 table 名排序, view 在最后
+fn sort_tables(tables: Vec<TableSchema>) -> Result<Vec<TableSchema>, String> {
+    let mut pending = BTreeMap::new();
+    let mut views = BTreeMap::new();
+    for table in tables {
+        let map = if table.view { &mut views } else { &mut pending };
+        if let Some(other) = map.insert(table.name.clone(), table) {
+            return Err(format!("table 名 {:?} 重复, {}", other.name, other.struct_name));
+        }
+    }
+
+    let mut sorted = Vec::new();
+    loop {
+        // 不依赖其他未排序表的, 引用自身或外部的表不计
+        let ready = pending
+            .values()
+            .filter(|table: &&TableSchema| {
+                table.columns.iter().all(|column| match &column.references {
+                    Some((name, _)) => name == &table.name || !pending.contains_key(name),
+                    None => true,
+                })
+            })
+            .map(|table| table.name.clone())
+            .collect::<Vec<String>>();
+        if ready.is_empty() {
+            break;
+        }
+        for name in ready {
+            sorted.extend(pending.remove(&name));
+        }
+    }
+
+    if !pending.is_empty() {
+        return Err(format!(
+            "外键循环依赖: {}",
+            pending.keys().cloned().collect::<Vec<String>>().join(", ")
+        ));
+    }
+
+    sorted.extend(views.into_values());
+    Ok(sorted)
+}
+
+/// schema.sql, 分表仅为模板, 以注释输出
+fn make_sql(tables: &[TableSchema]) -> String {
+    let mut sql = "-- 由 schema 根据 AsSqlModel 生成, 请勿手工修改\n".to_string();
+    for table in tables {
+        sql.push_str(&format!("\n-- {}\n", table.struct_name));
+        let mut lines = vec![table.create_table.clone()];
+        lines.extend(table.create_history_table.clone());
+        for line in lines {
+            if table.sharded {
+                sql.push_str("-- 分表模板, 使用 make_create_table_for 创建\n");
+                for x in line.lines() {
+                    sql.push_str(&format!("-- {}\n", x));
+                }
+            } else {
+                sql.push_str(&format!("{}\n", line));
+            }
+        }
+    }
+    sql
+}
+
+/// json 字符串
+fn json_string(x: &str) -> String {
+    let mut json = "\"".to_string();
+    for c in x.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if (c as u32) < 0x20 => json.push_str(&format!("\\u{:04x}", c as u32)),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
+
+/// json 字符串或 null
+fn json_option(x: &Option<String>) -> String {
+    x.as_deref().map(json_string).unwrap_or_else(|| "null".to_string())
+}
+
+/// schema.json, 顺序同 schema.sql
+fn make_json(tables: &[TableSchema]) -> String {
+    let tables = tables
+        .iter()
+        .map(|table| {
+            let columns = table
+                .columns
+                .iter()
+                .map(|column| {
+                    format!(
+                        "        {{\"field\": {}, \"name\": {}, \"type\": {}, \"key\": {}, \"auto\": {}, \"generated\": {}, \"references\": {}}}",
+                        json_string(&column.field),
+                        json_string(&column.name),
+                        json_string(&column.sql_type),
+                        json_option(&column.key),
+                        column.auto,
+                        json_option(&column.generated),
+                        match &column.references {
+                            Some((name, id)) => format!("{{\"table\": {}, \"column\": {}}}", json_string(name), json_string(id)),
+                            None => "null".to_string(),
+                        }
+                    )
+                })
+                .collect::<Vec<String>>()
+                .join(",\n");
+            format!(
+                "  {{\n    \"struct\": {},\n    \"table\": {},\n    \"view\": {},\n    \"sharded\": {},\n    \"audit\": {},\n    \"create_table\": {},\n    \"columns\": [\n{}\n    ]\n  }}",
+                json_string(&table.struct_name),
+                json_string(&table.name),
+                table.view,
+                table.sharded,
+                table.create_history_table.is_some(),
+                json_string(&table.create_table),
+                columns
+            )
+        })
+        .collect::<Vec<String>>()
+        .join(",\n");
+    format!("[\n{}\n]\n", tables)
+}
+
+fn main() {
+    let args = std::env::args().collect::<Vec<String>>();
+    if args.len() < 2 {
+        eprintln!("用法: schema <src 目录> [输出目录]");
+        exit(2);
+    }
+    let src = Path::new(&args[1]);
+    let out = Path::new(args.get(2).map(|x| x.as_str()).unwrap_or("."));
+
+    // 解析
+    let mut files = Vec::new();
+    if let Err(err) = list_files(src, &mut files) {
+        eprintln!("{}: {}", src.display(), err);
+        exit(1);
+    }
+    files.sort();
+
+    let mut tables = Vec::new();
+    let mut errors = 0;
+    for path in files {
+        let file = fs::read_to_string(&path)
+            .map_err(|err| err.to_string())
+            .and_then(|text| syn::parse_file(&text).map_err(|err| err.to_string()));
+        match file {
+            Ok(file) => errors += collect_models(&file.items, &path, &mut tables),
+            Err(err) => {
+                eprintln!("{}: {}", path.display(), err);
+                errors += 1;
+            }
+        }
+    }
+    if errors > 0 {
+        exit(1);
+    }
+
+    // 排序并输出
+    let tables = match sort_tables(tables) {
+        Ok(tables) => tables,
+        Err(err) => {
+            eprintln!("{}", err);
+            exit(1);
+        }
+    };
+    for (name, text) in [("schema.sql", make_sql(&tables)), ("schema.json", make_json(&tables))] {
+        let path = out.join(name);
+        if let Err(err) = fs::write(&path, text) {
+            eprintln!("{}: {}", path.display(), err);
+            exit(1);
+        }
+    }
+    println!("{} 个 table 写入 {}", tables.len(), out.display());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tables(input: &str) -> Vec<TableSchema> {
+        let file = syn::parse_file(input).unwrap();
+        let mut tables = Vec::new();
+        assert_eq!(collect_models(&file.items, Path::new("test.rs"), &mut tables), 0);
+        tables
+    }
+
+    fn names(tables: &[TableSchema]) -> Vec<&str> {
+        tables.iter().map(|table| table.name.as_str()).collect()
+    }
+
+    #[test]
+    fn sort_by_references() {
+        let sorted = sort_tables(tables(
+            r#"
+            #[derive(AsSqlModel)]
+            #[table(name = "adult", view = "SELECT `id` FROM `user`")]
+            struct Adult { id: u64 }
+
+            #[derive(AsSqlModel)]
+            #[table(name = "order_line")]
+            struct OrderLine {
+                id: u64,
+                #[column(references = "orders(id)")]
+                order_id: u64,
+                #[column(references = "item(id)")]
+                item_id: u64,
+            }
+
+            mod inner {
+                #[derive(AsSqlModel)]
+                #[table(name = "orders")]
+                struct Order {
+                    id: u64,
+                    #[column(references = "user(id)")]
+                    user_id: u64,
+                    #[column(references = "orders(id)")]
+                    parent_id: u64,
+                }
+            }
+
+            #[derive(AsSqlModel)]
+            #[table(name = "user")]
+            struct User { id: u64 }
+
+            #[derive(AsSqlModel)]
+            #[table(name = "item")]
+            struct Item {
+                id: u64,
+                #[column(references = "other_db(id)")]
+                other_id: u64,
+            }
+
+            #[derive(Debug)]
+            struct NotModel { id: u64 }
+            "#,
+        ))
+        .unwrap();
+        // 被引用的表在前, 引用自身及外部表不计, view 在最后
+        assert_eq!(names(&sorted), vec!["item", "user", "orders", "order_line", "adult"]);
+    }
+
+    #[test]
+    fn sort_errors() {
+        let err = sort_tables(tables(
+            r#"
+            #[derive(AsSqlModel)]
+            #[table(name = "a")]
+            struct A { id: u64, #[column(references = "b(id)")] b_id: u64 }
+
+            #[derive(AsSqlModel)]
+            #[table(name = "b")]
+            struct B { id: u64, #[column(references = "a(id)")] a_id: u64 }
+
+            #[derive(AsSqlModel)]
+            #[table(name = "c")]
+            struct C { id: u64 }
+            "#,
+        ))
+        .unwrap_err();
+        assert_eq!(err, "外键循环依赖: a, b");
+
+        let err = sort_tables(tables(
+            r#"
+            #[derive(AsSqlModel)]
+            #[table(name = "a")]
+            struct A { id: u64 }
+
+            #[derive(AsSqlModel)]
+            #[table(name = "a")]
+            struct B { id: u64 }
+            "#,
+        ))
+        .unwrap_err();
+        assert!(err.contains("重复"), "{}", err);
+    }
+
+    #[test]
+    fn json_escape() {
+        assert_eq!(json_string("a\"b\\c"), r#""a\"b\\c""#);
+        assert_eq!(json_string("a\nb\r\tc"), r#""a\nb\r\tc""#);
+        assert_eq!(json_string("\u{1}中"), r#""\u0001中""#);
+        assert_eq!(json_option(&None), "null");
+        assert_eq!(json_option(&Some("`a`".to_string())), r#""`a`""#);
+    }
+
+    #[test]
+    fn sql_and_json() {
+        let sorted = sort_tables(tables(
+            r#"
+            #[derive(AsSqlModel)]
+            #[table(name = "log_{tenant}")]
+            struct Log { id: u64, tenant: String }
+
+            #[derive(AsSqlModel)]
+            #[table(name = "user", audit)]
+            struct User { id: u64, #[column(key = "UNIQUE")] name: String }
+            "#,
+        ))
+        .unwrap();
+
+        // 分表以注释输出, audit 表同时输出 history 表
+        let sql = make_sql(&sorted);
+        assert!(sql.contains("-- 分表模板"));
+        assert_eq!(
+            sql.lines().filter(|x| x.starts_with("CREATE TABLE")).count(),
+            2,
+            "{}",
+            sql
+        );
+        assert!(sql.contains("`user_history`"));
+
+        let json = make_json(&sorted);
+        assert!(json.contains(r#""table": "log_{tenant}""#), "{}", json);
+        assert!(json.contains(r#""sharded": true"#));
+        assert!(json.contains(r#""audit": true"#));
+        assert!(json.contains(r#""key": "UNIQUE""#));
+        // create_table 中的换行转义后仍在同一行
+        assert!(
+            json.contains(r#""create_table": "CREATE TABLE `user` (\n    `id`"#),
+            "{}",
+            json
+        );
+    }
+}
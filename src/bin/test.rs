@@ -14,19 +14,21 @@ fn test3() {
 }
 
 /// test4, 内部 //! 的注释和这里的合并了
+#[allow(clippy::mixed_attributes_style)]
 fn test4() {
     //!
     //! auto_func_name 不允许这行注释, 直接报错 an inner attribute is not permitted in this context
 }
 
 /// test5, 内部 //! 的注释和这里的合并了
+#[allow(clippy::mixed_attributes_style)]
 fn test5() {
     //! auto_func_name 不允许这行注释, 直接报错 an inner attribute is not permitted in this context
 }
 
-/// test6
-///
-/// &mut || 使得 auto_func_name 不能给出准确的报错, 但可以定位到 test6()
+// test6
+//
+// &mut || 使得 auto_func_name 不能给出准确的报错, 但可以定位到 test6()
 // #[auto_func_name]
 // fn test6() {
 //     &mut |x: i8| x;
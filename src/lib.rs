@@ -5,23 +5,108 @@
 use {
     chrono::{Duration, Utc},
     proc_macro::TokenStream,
+    python_comm_macros_core::{asm, pc},
     quote::quote,
+    syn::{parse_macro_input, DeriveInput},
 };
 
 mod afn;
-mod asm;
 mod lp;
 mod qa;
 
-// #[table(name="")]
+// #[table(name="", who="", from_row="")]
+// #[table(crate="")]
+//    生成代码中 python_comm 的路径, 缺省 ::python_comm, 重新导出时使用
+// #[table(writer="", reader="", primary_reads_ms="1000")]
+//    writer 同 who, 用于 lock_writer() 及生成的写操作; reader 用于 SqlModel::lock() 及 page/scan_by_id/iter_all/verify_schema, 未指定时同 writer
+//    who/writer/reader 为类名或路径, 如 db::Primary
+//    指定 reader 时 SqlModel::create/update/delete 均覆盖为使用 writer, page_in 等 _in/_for 版本使用传入的连接
+//    force_primary_reads(duration) 使当前线程的 lock() 暂时返回 writer, 指定 reader 时写操作后自动调用
+//    primary_reads_ms 为写操作后改读 writer 的时长, 缺省 1000 毫秒
+//    from_row="name" | index, 按列名 (缺省) 或按字段顺序解码 row
+//    try_from_row 及 from_row (conn.exec::<Self> 使用) 出错时给出列名, from_row_opt 仅返回 FromRowError
+//    出错时已取出的值放回, try_from_row/from_row_opt 返回的 row 与传入的相同
+// #[table(projection(name="", columns="a,b"))]
+//    生成仅含部分字段的 struct, 带 make_fields_b 及 FromRow, 可重复
+// #[table(validate="fn")]
+//    fn(&Self) -> Result<(), String>, 整行校验
+// #[table(before_insert="fn", after_insert="fn", before_update="fn", after_load="fn")]
+//    before_insert/before_update/after_load: fn(&mut Self), after_insert: fn(&Self, Option<u64>)
+//    SqlModel::create()/create_with()/create_tx() 调用 before_insert 及 after_insert, update()/update_tx() 调用 before_update
+//    create()/update() 中的 before_insert/before_update 作用于副本, 指定时 model 需实现 Clone
+//    from_row_opt 调用 after_load
+//    有钩子/校验/audit/reader/分表时才覆盖 SqlModel::create/update/delete, 通过 DbPool::get_conn() 取得连接
+//    此时需 python_comm 0.4.1 及以上 (SqlModel 含 update/delete, DbPool 含 get_conn), 否则 create_with 等同 SqlModel::create
+//    create_tx/create_with_tx/update_tx/delete_tx 在指定的连接或事务中执行, 同样调用钩子
+// #[table(name="log_{yyyymm}_{tenant}", shard_by="created_at")]
+//    {字段} 取字段值, {yyyy}/{yyyymm}/{yyyymmdd} 取 shard_by 字段中的数字
+//    table_name_for(&self), table_name_with(vars), make_create_table_for(name), 得到的 table 名运行时检查, 不合法时报错
+//    create/update/delete 等使用 table_name_for(), 分表不生成 page/scan_by_id/iter_all/verify_schema/fixture_insert_sql/load_data_sql
+//    改用 page_in/scan_by_id_in/iter_all_in/verify_schema_for/fixture_insert_sql_for/load_data_sql_for 指定 table 名
 // #[column(auto="", key="", name="", type="")]
 //    auto=y => AUTO_INCREMENT
 //    key="" | PRIMARY | UNIQUE
+// #[column(references="user(id)")]
+//    外键, 缺省引用 id, schema <src 目录> [输出目录] 按外键依赖导出 schema.sql 及 schema.json
+//    schema 由 cargo install python_comm_macros --bin schema 安装, 本仓库中可用 cargo run --bin schema -- <src 目录>
+// #[table(rename_all="camelCase", prefix="t_")]
+//    rename_all: snake_case | camelCase | PascalCase | SCREAMING_SNAKE_CASE | kebab-case, 先转换再加 prefix
+//    仅作用于未指定 name 的字段, 命名参数中 - 等字符替换为 _, 如 `user-name`=:user_name
+// #[pc(skip)]
+//    AsSqlModel/QuickAssign/LimitPack 共用, 忽略该字段, AsSqlModel 构造时取 Default, 旧的 _renames_ 前缀同样有效
+// table 名及字段名仅允许字母数字下划线及 $, 不超过 64 字符, 非保留字, 否则编译报错
+//    #[table(allow_reserved)], #[column(allow_reserved)] 允许保留字
+// 以下方法名可能与字段的 setter 重名, 需在 table 中开启
+// #[table(fake)]
+//    fake(), fake_with(rng) 按字段类型、varchar 长度及 min/max 生成随机 obj, 其他类型取 Default
+//    重新生成直到通过 validate (含自定义 validate), 100 次仍未通过时 panic
+//    随机字符串难以匹配 regex, 未指定 fake 的 regex 字段不参与重试, 结果不保证匹配
+//    #[column(fake="fn")] 指定该字段的生成函数, fn(&mut R) -> T, R: FnMut() -> u64
+//    fixture_insert_sql(n) 生成 n 条随机数据的 INSERT sql, 共用一个随机数生成器
+// #[table(csv)]
+//    生成 csv_header(), to_csv_record(&self), from_csv_record(&StringRecord), 需引入 csv crate
+// #[table(load_data)]
+//    write_tsv(rows, writer, with_id) 及 load_data_sql(path, with_id) 用于 LOAD DATA 批量导入
+// #[table(scan)]
+//    scan_by_id(after, batch_size), iter_all(batch_size) 按 id 分批遍历大表
+// #[table(page)]
+//    page(filter, params, page, per_page) 分页读取, 返回 python_comm::use_sql::Page<Self>, 含 items 及 total/page/per_page/pages
+// #[table(diff)]
+//    diff(&self, other) 返回全部不同的 (列名, self 值, other 值)
+// #[table(verify_schema)]
+//    verify_schema(), verify_schema_for(conn, name) 与 information_schema.COLUMNS 比较字段名、类型、NULL 及顺序
+//    返回 Vec<(字段名, 差异, 期望, 实际)>, 差异为 missing/extra/type/nullable/order, compare_schema(columns) 仅比较
+//    整数忽略显示宽度, text(n) 与 tinytext/text/mediumtext/longtext 均视为一致
+// #[table(audit)]
+//    生成 make_create_history_table(), SqlModel::update/delete 及 update_tx/delete_tx 先将原记录写入 xxx_history
+//    update_tx_by/delete_tx_by 可指定 changed_by
+//    SqlModel::update/delete 在 start_transaction 开启的事务中执行, 出错时一并回滚; _tx 版本由调用者的事务保证
+// #[column(eq="skip", eq_epsilon=1e-9)]
+//    equal/equal_without_id/diff 中不比较该字段, 或按误差比较
+// #[column(validate="fn", min=0, max=100, regex="", not_empty)]
+//    validate: fn(&T) -> Result<(), String>, regex 需引入 regex crate, 无效的 regex 在校验时作为错误返回
+//    有校验规则时生成 validate(&self) -> Result<(), Vec<python_comm::use_sql::ValidationError>>, create/create_tx/update/update_tx 均校验
+//    ValidationError { column: Option<&'static str>, message }, table 的 validate 出错时 column 为 None, 需 python_comm 提供
+//    无校验规则的 model 不生成 validate/validation_errors, 不依赖 ValidationError
+// #[column(generated="price * qty", stored)]
+//    生成列, GENERATED ALWAYS AS (...) STORED, 不含 stored 时为 VIRTUAL, 不参与 insert/update
+// #[table(check="end_at > start_at")]
+//    make_create_table 中加入 CHECK 约束
+// features = ["registry"]
+//    每个非泛型 model 向 python_comm::inventory 注册 python_comm::use_sql::ModelInfo, 由 python_comm::all_models() 遍历
+//    ModelInfo { struct_name, table_name, view, sharded, create_table, create_history_table: Option, columns: &[ColumnInfo] }
+//    ColumnInfo { field, name, sql_type, key: Option, auto, generated: Option, references: Option<(table, column)> }
+//    均为 &'static str 或 bool, 需 python_comm 重新导出 inventory 并 collect!(ModelInfo)
+// #[table(view="SELECT ...")]
+//    只读 model, make_create_table 生成 CREATE VIEW, 不生成 create_with/update_tx/delete_tx 等写操作, SqlModel::create/update/delete 返回错误
 
 /// AsSqlModel
-#[proc_macro_derive(AsSqlModel, attributes(table, column))]
+#[proc_macro_derive(AsSqlModel, attributes(table, column, pc))]
 pub fn as_sql_model(input: TokenStream) -> TokenStream {
-    asm::as_sql_model(input)
+    // 解析输入
+    let dvi = parse_macro_input!(input as DeriveInput);
+
+    asm::expand(&dvi).into()
 }
 
 /// Create a local variable fname = "xxx" in fn xxx()
@@ -46,13 +131,13 @@ pub fn build_time(_input: TokenStream) -> TokenStream {
     TokenStream::from(quote!(#now))
 }
 
-#[proc_macro_derive(LimitPack)]
+#[proc_macro_derive(LimitPack, attributes(pc))]
 pub fn limit_pack(input: TokenStream) -> TokenStream {
     lp::limit_pack(input)
 }
 
 /// 包含 AsSqlModel 中的字段赋值部分, 在非 sql 中使用
-#[proc_macro_derive(QuickAssign)]
+#[proc_macro_derive(QuickAssign, attributes(pc))]
 pub fn quick_assign(input: TokenStream) -> TokenStream {
     qa::quick_assign(input)
 }
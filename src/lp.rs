@@ -4,6 +4,8 @@ use quote::quote;
 use std::iter::FromIterator;
 use syn::{parse_macro_input, Data, DeriveInput, Fields, Ident, Type};
 
+use crate::pc::is_skipped;
+
 /// 在全部字段上执行
 fn map_fields<F>(fields: &Fields, mapper: F) -> TokenStream2
 where
@@ -13,7 +15,7 @@ where
         fields
             .iter()
             .enumerate()
-            .filter(|x| !x.1.ident.as_ref().unwrap().to_string().starts_with("_renames_"))
+            .filter(|x| matches!(is_skipped(x.1), Ok(false)))
             .map(|x| (x.0, x.1.ident.as_ref().unwrap(), &x.1.ty, x.0 == fields.len() - 1))
             .map(mapper),
     )
@@ -32,6 +34,11 @@ pub fn limit_pack(input: TokenStream) -> TokenStream {
 
         // 仅处理命名成员变量
         if matches!(&fields, Fields::Named(_)) {
+            // #[pc(...)] 有误时报错
+            if let Some(err) = fields.iter().find_map(|field| is_skipped(field).err()) {
+                return err.to_compile_error().into();
+            }
+
             // 通过 map_fields 处理每个字段, 生成特定代码
 
             let field_to_limit_str = map_fields(&fields, |(_i, ident, _ty, _last)| {
@@ -1,4 +1,5 @@
 use {
+    crate::pc::is_skipped,
     proc_macro::TokenStream,
     proc_macro2::TokenStream as TokenStream2,
     quote::quote,
@@ -7,7 +8,7 @@ use {
 };
 
 /// 在全部字段上执行
-fn map_columns<M>(columns: &Vec<Column>, mut mapper: M) -> TokenStream2
+fn map_columns<M>(columns: &[Column], mut mapper: M) -> TokenStream2
 where
     M: FnMut(usize, &Ident, &Type) -> TokenStream2,
 {
@@ -24,7 +25,7 @@ pub fn quick_assign(input: TokenStream) -> TokenStream {
     let dvi = parse_macro_input!(input as DeriveInput);
 
     // 字段
-    let fields = match dvi.data {
+    let fields: Vec<Field> = match dvi.data {
         Data::Struct(DataStruct { ref fields, .. }) => fields.iter().cloned().collect(),
         _ => panic!("仅用于 struct"),
     };
@@ -33,12 +34,16 @@ pub fn quick_assign(input: TokenStream) -> TokenStream {
     let struct_ident = &dvi.ident;
 
     let mut table = Table::new();
-    table.parse_struct_fields(&fields);
+    if let Err(err) = table.parse_struct_fields(&fields) {
+        return err.to_compile_error().into();
+    }
 
     let make_assign = table.make_assign();
 
+    let (impl_generics, ty_generics, where_clause) = dvi.generics.split_for_impl();
+
     let impl_ast = quote!(
-        impl #struct_ident {
+        impl #impl_generics #struct_ident #ty_generics #where_clause {
             #make_assign
         }
     );
@@ -73,9 +78,9 @@ impl Table {
     fn make_assign(&self) -> TokenStream2 {
         map_columns(&self.columns, |_i, ident, ty| {
             quote!(
-                pub fn #ident<T>(mut self, v: T) -> Self
+                pub fn #ident<__T>(mut self, v: __T) -> Self
                 where
-                    T: Into<#ty>,
+                    __T: Into<#ty>,
                 {
                     self.#ident = v.into();
                     self
@@ -90,11 +95,14 @@ impl Table {
     }
 
     /// 解析 struct fields 属性
-    fn parse_struct_fields(&mut self, fields: &Vec<Field>) {
-        // 遍历每个 field
+    fn parse_struct_fields(&mut self, fields: &[Field]) -> Result<(), syn::Error> {
+        // 遍历每个 field, 忽略 #[pc(skip)]
         for field in fields {
-            // 记录
-            self.columns.push(Column::new(field.clone()));
+            if !is_skipped(field)? {
+                // 记录
+                self.columns.push(Column::new(field.clone()));
+            }
         }
+        Ok(())
     }
 }
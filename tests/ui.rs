@@ -0,0 +1,10 @@
+// AsSqlModel 生成的代码依赖 python_comm 及 mysql, python_comm 又依赖本 crate,
+// pass/asm_*.rs 用 tests/ui/stub 中的最小实现代替, 并以 #[table(crate = "crate::stub")] 指定
+// 生成的 sql 见 core/src/asm.rs 中的 snapshot 测试
+
+#[test]
+fn ui() {
+    let t = trybuild::TestCases::new();
+    t.pass("tests/ui/pass/*.rs");
+    t.compile_fail("tests/ui/fail/*.rs");
+}
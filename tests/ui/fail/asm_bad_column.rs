@@ -0,0 +1,13 @@
+use python_comm_macros::AsSqlModel;
+
+#[derive(AsSqlModel)]
+#[table(name = "user")]
+struct User {
+    id: u64,
+    #[column(name = "user-name")]
+    name: String,
+    #[column(name = "select")]
+    kind: i32,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use python_comm_macros::AsSqlModel;
+
+#[derive(AsSqlModel)]
+#[table(name = "a", before_insert = "hooks::", after_load = "lower case")]
+struct A {
+    id: u64,
+}
+
+fn main() {}
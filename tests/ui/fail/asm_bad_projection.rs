@@ -0,0 +1,9 @@
+use python_comm_macros::AsSqlModel;
+
+#[derive(AsSqlModel)]
+#[table(name = "a", projection(name = "1P", columns = "id"), projection(name = "Q", columns = "id, age"))]
+struct A {
+    id: u64,
+}
+
+fn main() {}
@@ -0,0 +1,16 @@
+use python_comm_macros::AsSqlModel;
+
+#[derive(AsSqlModel)]
+#[table(name = "a", rename_all = "lowercase")]
+struct A {
+    id: u64,
+}
+
+#[derive(AsSqlModel)]
+#[table(name = "b", rename_all = "kebab-case", prefix = "b-")]
+struct B {
+    id: u64,
+    first_name: String,
+}
+
+fn main() {}
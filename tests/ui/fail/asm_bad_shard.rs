@@ -0,0 +1,23 @@
+use python_comm_macros::AsSqlModel;
+
+#[derive(AsSqlModel)]
+#[table(name = "log_{yyyymm}")]
+struct A {
+    id: u64,
+    created_at: String,
+}
+
+#[derive(AsSqlModel)]
+#[table(name = "log_{yyyymm}", shard_by = "created")]
+struct B {
+    id: u64,
+    created_at: String,
+}
+
+#[derive(AsSqlModel)]
+#[table(name = "log_{tenant}")]
+struct C {
+    id: u64,
+}
+
+fn main() {}
@@ -0,0 +1,13 @@
+use python_comm_macros::AsSqlModel;
+
+#[derive(AsSqlModel)]
+#[table(name = "a", validate = "check a")]
+struct A {
+    id: u64,
+    #[column(min = "zero", max = 150)]
+    age: i32,
+    #[column(validate = "1")]
+    name: String,
+}
+
+fn main() {}
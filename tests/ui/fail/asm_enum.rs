@@ -0,0 +1,8 @@
+use python_comm_macros::AsSqlModel;
+
+#[derive(AsSqlModel)]
+enum User {
+    A,
+}
+
+fn main() {}
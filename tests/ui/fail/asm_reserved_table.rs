@@ -0,0 +1,9 @@
+use python_comm_macros::AsSqlModel;
+
+#[derive(AsSqlModel)]
+#[table(name = "order")]
+struct Order {
+    id: u64,
+}
+
+fn main() {}
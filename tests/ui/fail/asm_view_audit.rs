@@ -0,0 +1,9 @@
+use python_comm_macros::AsSqlModel;
+
+#[derive(AsSqlModel)]
+#[table(name = "adult", view = "SELECT `id` FROM `user` WHERE `age` >= 18", audit)]
+struct Adult {
+    id: u64,
+}
+
+fn main() {}
@@ -0,0 +1,17 @@
+use python_comm_macros::{AsSqlModel, QuickAssign};
+
+#[derive(QuickAssign)]
+struct User {
+    #[pc(skipp)]
+    name: String,
+}
+
+#[derive(AsSqlModel)]
+#[table(name = "item")]
+struct Item {
+    id: u64,
+    #[pc = "skip"]
+    cache: String,
+}
+
+fn main() {}
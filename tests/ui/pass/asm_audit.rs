@@ -0,0 +1,59 @@
+#[path = "../stub/mysql.rs"]
+mod mysql;
+#[path = "../stub/python_comm.rs"]
+mod stub;
+
+use python_comm_macros::AsSqlModel;
+use std::sync::{Mutex, MutexGuard, OnceLock};
+use stub::{use_m::MoreError, use_sql::DbPool, use_sql::SqlModel};
+
+struct WhoCreateDbPool;
+
+impl WhoCreateDbPool {
+    fn lock() -> Result<MutexGuard<'static, DbPool>, MoreError> {
+        static POOL: OnceLock<Mutex<DbPool>> = OnceLock::new();
+        Ok(POOL
+            .get_or_init(|| Mutex::new(DbPool { conn: mysql::Conn }))
+            .lock()
+            .unwrap())
+    }
+}
+
+#[derive(AsSqlModel, Clone, Debug, Default)]
+#[table(crate = "crate::stub", name = "price", audit)]
+struct Price {
+    id: u64,
+    value: f64,
+}
+
+fn main() {
+    let price = Price::default().id(1u64).value(2.0);
+
+    // history 先于 UPDATE/DELETE, 在同一事务中提交
+    mysql::take_log();
+    assert!(price.update().is_ok());
+    let log = mysql::take_log();
+    assert_eq!(log.len(), 4);
+    assert_eq!(log[0], "START TRANSACTION");
+    assert!(log[1].starts_with("INSERT INTO `price_history`"));
+    assert!(log[2].starts_with("UPDATE `price`"));
+    assert_eq!(log[3], "COMMIT");
+
+    assert!(price.delete().is_ok());
+    let log = mysql::take_log();
+    assert!(log[1].starts_with("INSERT INTO `price_history`"));
+    assert!(log[2].starts_with("DELETE FROM `price`"));
+    assert_eq!(log[3], "COMMIT");
+
+    // UPDATE/DELETE 失败时回滚, 不留下 history
+    mysql::fail_on("UPDATE");
+    assert!(price.update().is_err());
+    let log = mysql::take_log();
+    assert!(log[1].starts_with("INSERT INTO `price_history`"));
+    assert!(log[2].starts_with("UPDATE `price`"));
+    assert_eq!(log[3], "ROLLBACK");
+
+    mysql::fail_on("DELETE");
+    assert!(price.delete().is_err());
+    assert_eq!(mysql::take_log().last().unwrap(), "ROLLBACK");
+}
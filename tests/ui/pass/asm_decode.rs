@@ -0,0 +1,81 @@
+#[path = "../stub/mysql.rs"]
+mod mysql;
+#[path = "../stub/python_comm.rs"]
+mod stub;
+
+use mysql::{prelude::FromRow, Row, Value};
+use python_comm_macros::AsSqlModel;
+use std::sync::{Mutex, MutexGuard, OnceLock};
+use stub::{use_m::MoreError, use_sql::DbPool};
+
+struct WhoCreateDbPool;
+
+impl WhoCreateDbPool {
+    fn lock() -> Result<MutexGuard<'static, DbPool>, MoreError> {
+        static POOL: OnceLock<Mutex<DbPool>> = OnceLock::new();
+        Ok(POOL
+            .get_or_init(|| Mutex::new(DbPool { conn: mysql::Conn }))
+            .lock()
+            .unwrap())
+    }
+}
+
+#[derive(AsSqlModel, Clone, Debug, Default)]
+#[table(crate = "crate::stub", name = "user")]
+struct User {
+    id: u64,
+    #[column(name = "user_name")]
+    name: String,
+    age: i32,
+}
+
+#[derive(AsSqlModel, Clone, Debug, Default)]
+#[table(crate = "crate::stub", name = "pos", from_row = "index")]
+struct Pos {
+    id: u64,
+    x: i32,
+}
+
+fn main() {
+    // 按列名解码, 与列的顺序无关
+    let row = Row::new(
+        &["age", "user_name", "id"],
+        vec![Value::Int(30), "bob".into(), Value::Int(1)],
+    );
+    let user = User::try_from_row(row).unwrap();
+    assert_eq!((user.id, user.name.as_str(), user.age), (1, "bob", 30));
+
+    // 缺少的列, 返回列名及原 row, 已取出的值放回
+    let row = Row::new(&["id", "age"], vec![Value::Int(1), Value::Int(30)]);
+    let (column, row) = User::try_from_row(row).unwrap_err();
+    assert_eq!(column, "user_name");
+    assert_eq!(row.get::<Value, _>("id"), Some(Value::Int(1)));
+
+    // 转换失败, 返回列名, 原 row 保持不变, from_row 报告同一列
+    let row = Row::new(
+        &["id", "user_name", "age"],
+        vec![Value::Int(1), "bob".into(), "thirty".into()],
+    );
+    let (column, row) = User::try_from_row(row).unwrap_err();
+    assert_eq!(column, "age");
+    assert_eq!(row.get::<Value, _>("id"), Some(Value::Int(1)));
+    assert_eq!(row.get::<Value, _>("user_name"), Some("bob".into()));
+    assert_eq!(row.get::<Value, _>("age"), Some("thirty".into()));
+    assert!(User::from_row_opt(row.clone()).is_err());
+
+    // conn.exec::<Self> 走 from_row, panic 信息中含列名
+    std::panic::set_hook(Box::new(|_| {}));
+    let message = std::panic::catch_unwind(|| User::from_row(row))
+        .unwrap_err()
+        .downcast::<String>()
+        .unwrap();
+    let _ = std::panic::take_hook();
+    assert!(message.contains("User 解码列 age 失败"), "{}", message);
+
+    // 按字段顺序解码, 不看列名
+    let row = Row::new(&["a", "b"], vec![Value::Int(1), Value::Int(3)]);
+    assert_eq!(Pos::try_from_row(row).unwrap().x, 3);
+    let row = Row::new(&["a", "b"], vec![Value::Int(1), "x".into()]);
+    let (column, row) = Pos::try_from_row(row).unwrap_err();
+    assert_eq!((column, row.get::<Value, _>(0)), ("x", Some(Value::Int(1))));
+}
@@ -0,0 +1,76 @@
+#[path = "../stub/mysql.rs"]
+mod mysql;
+#[path = "../stub/python_comm.rs"]
+mod stub;
+
+use mysql::{Row, Value};
+use python_comm_macros::AsSqlModel;
+use std::sync::{Mutex, MutexGuard, OnceLock};
+use stub::{use_m::MoreError, use_sql::DbPool, use_sql::SqlModel};
+
+struct WhoCreateDbPool;
+
+impl WhoCreateDbPool {
+    fn lock() -> Result<MutexGuard<'static, DbPool>, MoreError> {
+        static POOL: OnceLock<Mutex<DbPool>> = OnceLock::new();
+        Ok(POOL
+            .get_or_init(|| Mutex::new(DbPool { conn: mysql::Conn }))
+            .lock()
+            .unwrap())
+    }
+}
+
+// 字段类型为泛型, 投影只含用到的泛型参数
+#[derive(AsSqlModel, Clone, Debug, Default)]
+#[table(
+    crate = "crate::stub",
+    name = "setting",
+    projection(name = "SettingName", columns = "id, name"),
+    projection(name = "SettingValue", columns = "id, value")
+)]
+struct Setting<T> {
+    id: u64,
+    name: String,
+    value: T,
+}
+
+// 生命周期只用于不入库的字段
+#[derive(AsSqlModel, Clone, Debug, Default)]
+#[table(crate = "crate::stub", name = "named", projection(name = "NamedTitle", columns = "title"))]
+struct Named<'a> {
+    id: u64,
+    title: String,
+    #[pc(skip)]
+    source: Option<&'a str>,
+}
+
+fn main() {
+    // 泛型 model
+    let setting = Setting::<i32>::default().name("limit").value(10);
+    assert!(setting.equal(&setting.clone()));
+    assert!(!setting.equal_without_id(&setting.clone().value(11)));
+    assert!(Setting::<i32>::create_with("limit".to_string(), 10).is_ok());
+    let row = Row::new(&["id", "name", "value"], vec![Value::Int(1), "a".into(), "b".into()]);
+    let setting = Setting::<String>::try_from_row(row).unwrap();
+    assert_eq!((setting.id, setting.value.as_str()), (1, "b"));
+
+    // 投影
+    let row = Row::new(&["id", "name"], vec![Value::Int(1), "limit".into()]);
+    let name: SettingName = SettingName::try_from_row(row).unwrap();
+    assert_eq!(name.name, "limit");
+    let row = Row::new(&["id", "value"], vec![Value::Int(1), Value::Double(0.5)]);
+    let value: SettingValue<f64> = SettingValue::try_from_row(row).unwrap();
+    assert_eq!(value.value, 0.5);
+    assert_eq!(SettingValue::<f64>::make_fields_b(), "`id`, `value`");
+
+    // 带生命周期的 model
+    let text = String::from("import");
+    let named = Named { source: Some(&text), ..Default::default() }.title("a");
+    assert_eq!(named.source, Some("import"));
+    assert_eq!(Named::table_name(), "named");
+    let row = Row::new(&["id", "title"], vec![Value::Int(2), "b".into()]);
+    let named = Named::try_from_row(row).unwrap();
+    assert_eq!((named.id, named.title.as_str(), named.source), (2, "b", None));
+    let row = Row::new(&["title"], vec!["c".into()]);
+    assert_eq!(NamedTitle::try_from_row(row).unwrap().title, "c");
+}
@@ -0,0 +1,153 @@
+#[path = "../stub/mysql.rs"]
+mod mysql;
+#[path = "../stub/python_comm.rs"]
+mod stub;
+
+use python_comm_macros::AsSqlModel;
+use std::sync::{Mutex, MutexGuard, OnceLock};
+use mysql::Value;
+use stub::{use_m::MoreError, use_sql::DbPool, use_sql::SqlModel};
+
+struct WhoCreateDbPool;
+
+impl WhoCreateDbPool {
+    fn lock() -> Result<MutexGuard<'static, DbPool>, MoreError> {
+        static POOL: OnceLock<Mutex<DbPool>> = OnceLock::new();
+        Ok(POOL
+            .get_or_init(|| Mutex::new(DbPool { conn: mysql::Conn }))
+            .lock()
+            .unwrap())
+    }
+}
+
+mod pools {
+    use super::*;
+
+    // 只读副本
+    pub struct Replica;
+
+    impl Replica {
+        pub fn lock() -> Result<MutexGuard<'static, DbPool>, MoreError> {
+            static POOL: OnceLock<Mutex<DbPool>> = OnceLock::new();
+            Ok(POOL
+                .get_or_init(|| Mutex::new(DbPool { conn: mysql::Conn }))
+                .lock()
+                .unwrap())
+        }
+    }
+}
+
+fn lower(user: &mut User) {
+    user.name = user.name.to_lowercase();
+}
+
+fn upper(user: &mut User) {
+    user.name = user.name.to_uppercase();
+}
+
+#[derive(AsSqlModel, Clone, Debug, Default)]
+#[table(crate = "crate::stub", name = "user", before_insert = "lower", after_load = "upper", fake, page, scan, diff)]
+struct User {
+    #[column(auto, key = "PRIMARY")]
+    id: u64,
+    #[column(name = "user_name", key = "UNIQUE", not_empty)]
+    name: String,
+    #[column(min = 0, max = 150)]
+    age: i32,
+    score: Option<f64>,
+}
+
+#[derive(AsSqlModel, Clone, Debug, Default)]
+#[table(
+    crate = "crate::stub",
+    name = "log_{tenant}",
+    writer = "crate::WhoCreateDbPool",
+    reader = "pools::Replica",
+    primary_reads_ms = "10"
+)]
+struct Log {
+    id: u64,
+    tenant: String,
+}
+
+fn coupon_code<R: FnMut() -> u64>(rng: &mut R) -> String {
+    format!("C{:03}", rng() % 1000)
+}
+
+#[derive(AsSqlModel, Clone, Debug, Default)]
+#[table(crate = "crate::stub", name = "coupon", fake)]
+struct Coupon {
+    id: u64,
+    #[column(fake = "coupon_code", not_empty)]
+    code: String,
+}
+
+fn main() {
+    assert_eq!(User::table_name(), "user");
+    assert!(User::make_create_table().starts_with("CREATE TABLE"));
+
+    // 校验规则
+    let user = User::default().name("a").age(200);
+    let errors = user.validate().unwrap_err();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].column, Some("age"));
+    assert!(User::fake().validate().is_ok());
+    assert!(Coupon::fake().code.starts_with('C'));
+
+    // 写操作经 stub 连接完成
+    mysql::take_log();
+    mysql::push_rows(&["LAST_INSERT_ID()"], vec![vec![Value::Int(7)]]);
+    assert_eq!(User::create_with("A".to_string(), 1, None).unwrap(), Some(7));
+    let log = mysql::take_log();
+    assert!(log[0].starts_with("INSERT INTO `user` (`user_name`, `age`, `score`)"));
+    assert_eq!(log[1], "SELECT LAST_INSERT_ID()");
+    assert!(user.update().is_err());
+    assert!(mysql::take_log().is_empty());
+    // 无 audit 时 delete 为 python_comm 的缺省实现
+    assert!(User::default().name("b").age(1).delete().is_ok());
+    assert!(mysql::take_log().is_empty());
+    let mut db = WhoCreateDbPool::lock().unwrap();
+    assert!(User::default().delete_tx(db.get_conn().unwrap()).is_ok());
+    drop(db);
+    assert_eq!(mysql::take_log(), vec!["DELETE FROM `user` WHERE `id`=:id"]);
+    mysql::fail_on("UPDATE");
+    assert!(User::default().name("b").age(1).update().is_err());
+    mysql::take_log();
+
+    // 分表名运行时检查
+    let log = Log::default().tenant("t1");
+    assert_eq!(log.table_name_for().unwrap(), "log_t1");
+    assert!(Log::table_name_with(&[("tenant", "x`; DROP")]).is_err());
+
+    // 无记录时的分页
+    let page = User::page("", (), 1, 10).unwrap();
+    assert_eq!((page.items.len(), page.total, page.pages), (0, 0, 0));
+
+    // 分页解码返回的行, 并调用 after_load
+    let columns = ["id", "user_name", "age", "score"];
+    mysql::take_log();
+    mysql::push_rows(&["COUNT(*)"], vec![vec![Value::Int(3)]]);
+    mysql::push_rows(
+        &columns,
+        vec![
+            vec![Value::Int(1), "bob".into(), Value::Int(30), Value::NULL],
+            vec![Value::Int(2), "amy".into(), Value::Int(20), Value::Double(1.5)],
+        ],
+    );
+    let page = User::page("`age` > ?", (18,), 1, 2).unwrap();
+    assert_eq!((page.items.len(), page.total, page.pages), (2, 3, 2));
+    assert_eq!(page.items[0].name, "BOB");
+    assert_eq!(page.items[1].score, Some(1.5));
+    assert_eq!(mysql::take_log()[0], "SELECT COUNT(*) FROM `user` WHERE `age` > ?");
+
+    // 按 id 分批遍历, 不足 batch_size 时结束
+    let row = |id: i64| vec![Value::Int(id), "c".into(), Value::Int(1), Value::NULL];
+    mysql::push_rows(&columns, vec![row(1), row(2)]);
+    mysql::push_rows(&columns, vec![row(3)]);
+    let ids = User::iter_all(2).map(|user| user.unwrap().id).collect::<Vec<_>>();
+    assert_eq!(ids, vec![1, 2, 3]);
+    assert_eq!(mysql::take_log().len(), 2);
+
+    let other = User::default().name("b");
+    assert_eq!(User::default().diff(&other).len(), 1);
+}
@@ -0,0 +1,97 @@
+#[path = "../stub/mysql.rs"]
+mod mysql;
+#[path = "../stub/python_comm.rs"]
+mod stub;
+
+use python_comm_macros::AsSqlModel;
+use std::sync::{Mutex, MutexGuard, OnceLock};
+use stub::{use_m::MoreError, use_sql::DbPool};
+
+struct WhoCreateDbPool;
+
+impl WhoCreateDbPool {
+    fn lock() -> Result<MutexGuard<'static, DbPool>, MoreError> {
+        static POOL: OnceLock<Mutex<DbPool>> = OnceLock::new();
+        Ok(POOL
+            .get_or_init(|| Mutex::new(DbPool { conn: mysql::Conn }))
+            .lock()
+            .unwrap())
+    }
+}
+
+// AsSqlModel 类型 text 即 text(65535)
+#[allow(non_camel_case_types)]
+type text = String;
+
+#[derive(AsSqlModel, Clone, Debug, Default)]
+#[table(crate = "crate::stub", name = "post", verify_schema)]
+struct Post {
+    id: u64,
+    title: String,
+    body: text,
+    #[column(type = "longtext")]
+    raw: String,
+    draft: bool,
+}
+
+fn schema(columns: &[(&str, &str)]) -> Vec<(String, String, String)> {
+    columns
+        .iter()
+        .map(|(name, ty)| (name.to_string(), ty.to_string(), "NO".to_string()))
+        .collect()
+}
+
+fn main() {
+    // MySQL 8 返回的类型: 整数无显示宽度, text(65535) 存为 mediumtext 等
+    for body in ["tinytext", "text", "mediumtext", "longtext"] {
+        let columns = schema(&[
+            ("id", "int"),
+            ("title", "varchar(32)"),
+            ("body", body),
+            ("raw", "longtext"),
+            ("draft", "tinyint(1)"),
+        ]);
+        assert_eq!(Post::compare_schema(&columns), vec![], "{}", body);
+    }
+
+    // 指定 longtext 时不接受其他 text
+    let columns = schema(&[
+        ("id", "int(20)"),
+        ("title", "varchar(32)"),
+        ("body", "text"),
+        ("raw", "text"),
+        ("draft", "tinyint(1)"),
+    ]);
+    assert_eq!(
+        Post::compare_schema(&columns),
+        vec![("raw".to_string(), "type", "longtext".to_string(), "text".to_string())]
+    );
+
+    // 缺少、多出、顺序及 NULL
+    let mut columns = schema(&[
+        ("id", "int"),
+        ("body", "varchar(32)"),
+        ("title", "varchar(32)"),
+        ("raw", "longtext"),
+        ("extra", "int"),
+    ]);
+    columns[3].2 = "YES".to_string();
+    let report = Post::compare_schema(&columns)
+        .into_iter()
+        .map(|(column, kind, _, _)| format!("{} {}", column, kind))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        report,
+        vec!["title order", "body type", "body order", "raw nullable", "draft missing", "extra extra"]
+    );
+
+    // verify_schema 读取 information_schema 返回的行
+    mysql::push_rows(
+        &["COLUMN_NAME", "COLUMN_TYPE", "IS_NULLABLE"],
+        schema(&[("id", "int"), ("title", "varchar(32)"), ("body", "mediumtext"), ("raw", "longtext"), ("draft", "tinyint(1)")])
+            .into_iter()
+            .map(|(a, b, c)| vec![a.into(), b.into(), c.into()])
+            .collect(),
+    );
+    assert_eq!(Post::verify_schema().unwrap(), vec![]);
+}
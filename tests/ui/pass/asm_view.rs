@@ -0,0 +1,37 @@
+#[path = "../stub/mysql.rs"]
+mod mysql;
+#[path = "../stub/python_comm.rs"]
+mod stub;
+
+use python_comm_macros::AsSqlModel;
+use std::sync::{Mutex, MutexGuard, OnceLock};
+use stub::{use_m::MoreError, use_sql::DbPool, use_sql::SqlModel};
+
+struct WhoCreateDbPool;
+
+impl WhoCreateDbPool {
+    fn lock() -> Result<MutexGuard<'static, DbPool>, MoreError> {
+        static POOL: OnceLock<Mutex<DbPool>> = OnceLock::new();
+        Ok(POOL
+            .get_or_init(|| Mutex::new(DbPool { conn: mysql::Conn }))
+            .lock()
+            .unwrap())
+    }
+}
+
+#[derive(AsSqlModel, Clone, Debug, Default)]
+#[table(crate = "crate::stub", name = "adult", view = "SELECT `id`, `age` FROM `user` WHERE `age` >= 18")]
+struct Adult {
+    id: u64,
+    age: i32,
+}
+
+fn main() {
+    assert!(Adult::make_create_table().starts_with("CREATE VIEW"));
+
+    // view 只读
+    let adult = Adult::default();
+    assert!(adult.create().is_err());
+    assert!(adult.update().is_err());
+    assert!(adult.delete().is_err());
+}
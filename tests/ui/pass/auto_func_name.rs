@@ -0,0 +1,10 @@
+use python_comm_macros::auto_func_name;
+
+#[auto_func_name]
+fn test() -> &'static str {
+    fname
+}
+
+fn main() {
+    assert_eq!(test(), "test");
+}
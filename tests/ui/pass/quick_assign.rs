@@ -0,0 +1,24 @@
+use python_comm_macros::QuickAssign;
+
+struct Cache;
+
+#[derive(QuickAssign, Default)]
+struct User {
+    id: u64,
+    name: String,
+    #[pc(skip)]
+    #[allow(dead_code)]
+    cache: Option<Cache>,
+}
+
+impl User {
+    // #[pc(skip)] 的字段没有生成同名的 setter
+    fn cache(self) -> Self {
+        self
+    }
+}
+
+fn main() {
+    let user = User::default().name("a").cache();
+    assert_eq!((user.id, user.name.as_str()), (0, "a"));
+}
@@ -0,0 +1,375 @@
+// 仿 mysql 20 的最小 API, 仅用于验证 AsSqlModel 生成的代码能编译运行
+// 执行的语句记录在 log 中, query/exec 依次返回 push_rows 排队的结果, fail_on 使匹配的语句出错
+#![allow(dead_code, unreachable_patterns)]
+
+use std::borrow::Cow;
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    NULL,
+    Bytes(Vec<u8>),
+    Int(i64),
+    UInt(u64),
+    Float(f32),
+    Double(f64),
+    Date(u16, u8, u8, u8, u8, u8, u32),
+    Time(bool, u32, u8, u8, u8, u32),
+}
+impl From<i32> for Value {
+    fn from(x: i32) -> Self {
+        Value::Int(x as i64)
+    }
+}
+impl From<i64> for Value {
+    fn from(x: i64) -> Self {
+        Value::Int(x)
+    }
+}
+impl From<u64> for Value {
+    fn from(x: u64) -> Self {
+        Value::UInt(x)
+    }
+}
+impl From<f64> for Value {
+    fn from(x: f64) -> Self {
+        Value::Double(x)
+    }
+}
+impl From<String> for Value {
+    fn from(x: String) -> Self {
+        Value::Bytes(x.into_bytes())
+    }
+}
+impl From<&str> for Value {
+    fn from(x: &str) -> Self {
+        Value::Bytes(x.as_bytes().to_vec())
+    }
+}
+impl From<bool> for Value {
+    fn from(x: bool) -> Self {
+        Value::Int(x as i64)
+    }
+}
+impl<T: Into<Value>> From<Option<T>> for Value {
+    fn from(x: Option<T>) -> Self {
+        x.map(Into::into).unwrap_or(Value::NULL)
+    }
+}
+impl Value {
+    pub fn as_sql(&self, _no_backslash: bool) -> String {
+        format!("{:?}", self)
+    }
+}
+#[derive(Debug)]
+pub struct FromValueError(pub Value);
+#[derive(Debug)]
+pub struct FromRowError(pub Row);
+#[derive(Debug)]
+pub struct Error;
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "e")
+    }
+}
+pub type Result<T> = std::result::Result<T, Error>;
+#[derive(Debug, Clone)]
+pub struct Column {
+    name: String,
+}
+impl Column {
+    pub fn name_str(&self) -> Cow<'_, str> {
+        Cow::Borrowed(&self.name)
+    }
+}
+#[derive(Clone, Debug)]
+pub struct Row {
+    values: Vec<Option<Value>>,
+    columns: std::sync::Arc<[Column]>,
+}
+impl Row {
+    pub fn new(names: &[&str], values: Vec<Value>) -> Self {
+        Row {
+            values: values.into_iter().map(Some).collect(),
+            columns: names
+                .iter()
+                .map(|n| Column { name: n.to_string() })
+                .collect::<Vec<_>>()
+                .into(),
+        }
+    }
+    pub fn columns_ref(&self) -> &[Column] {
+        &self.columns
+    }
+    pub fn take<T: prelude::FromValue, I: ColumnIndex>(&mut self, i: I) -> Option<T> {
+        let i = i.idx(&self.columns)?;
+        self.values.get_mut(i).and_then(|x| x.take()).map(T::from_value)
+    }
+    pub fn place(&mut self, i: usize, v: Value) {
+        self.values[i] = Some(v)
+    }
+    pub fn get<T: prelude::FromValue, I: ColumnIndex>(&self, i: I) -> Option<T> {
+        let i = i.idx(&self.columns)?;
+        self.values.get(i).and_then(|x| x.clone()).map(T::from_value)
+    }
+}
+pub trait ColumnIndex {
+    fn idx(&self, c: &[Column]) -> Option<usize>;
+}
+impl ColumnIndex for usize {
+    fn idx(&self, _c: &[Column]) -> Option<usize> {
+        Some(*self)
+    }
+}
+impl ColumnIndex for &str {
+    fn idx(&self, c: &[Column]) -> Option<usize> {
+        c.iter().position(|x| x.name == *self)
+    }
+}
+pub mod params {
+    use super::Value;
+    pub enum Params {
+        Empty,
+        Named(std::collections::HashMap<String, Value>),
+        Positional(Vec<Value>),
+    }
+    impl<'a> From<Vec<(&'a str, Value)>> for Params {
+        fn from(v: Vec<(&'a str, Value)>) -> Self {
+            Params::Named(v.into_iter().map(|(k, v)| (k.to_string(), v)).collect())
+        }
+    }
+    impl From<Vec<(String, Value)>> for Params {
+        fn from(v: Vec<(String, Value)>) -> Self {
+            Params::Named(v.into_iter().collect())
+        }
+    }
+    impl From<()> for Params {
+        fn from(_: ()) -> Self {
+            Params::Empty
+        }
+    }
+    impl From<Vec<Value>> for Params {
+        fn from(v: Vec<Value>) -> Self {
+            Params::Positional(v)
+        }
+    }
+    macro_rules! tp { ($($t:ident),*) => { impl<$($t: Into<Value>),*> From<($($t,)*)> for Params { #[allow(non_snake_case)] fn from(($($t,)*): ($($t,)*)) -> Self { Params::Positional(vec![$($t.into()),*]) } } } }
+    tp!(A);
+    tp!(A, B);
+    tp!(A, B, C);
+    tp!(A, B, C, D);
+}
+pub use params::Params;
+pub mod prelude {
+    use super::*;
+    pub trait ConvIr<T>: Sized {
+        fn new(v: Value) -> std::result::Result<Self, FromValueError>;
+        fn commit(self) -> T;
+        fn rollback(self) -> Value;
+    }
+    pub trait FromValue: Sized {
+        type Intermediate: ConvIr<Self>;
+        fn from_value(v: Value) -> Self {
+            Self::from_value_opt(v).ok().unwrap()
+        }
+        fn from_value_opt(v: Value) -> std::result::Result<Self, FromValueError> {
+            Self::get_intermediate(v).map(ConvIr::commit)
+        }
+        fn get_intermediate(v: Value) -> std::result::Result<Self::Intermediate, FromValueError> {
+            Self::Intermediate::new(v)
+        }
+    }
+    // 保留原值, rollback 时返回
+    pub struct Ir<T>(T, Value);
+    macro_rules! fv {
+        ($t:ty, $p:pat => $e:expr) => {
+            impl ConvIr<$t> for Ir<$t> {
+                fn new(v: Value) -> std::result::Result<Self, FromValueError> {
+                    let raw = v.clone();
+                    match v {
+                        $p => Ok(Ir($e, raw)),
+                        v => Err(FromValueError(v)),
+                    }
+                }
+                fn commit(self) -> $t {
+                    self.0
+                }
+                fn rollback(self) -> Value {
+                    self.1
+                }
+            }
+            impl FromValue for $t {
+                type Intermediate = Ir<$t>;
+            }
+        };
+    }
+    fv!(i32, Value::Int(x) => x as i32);
+    fv!(u32, Value::Int(x) => x as u32);
+    fv!(i64, Value::Int(x) => x);
+    fv!(u64, Value::Int(x) => x as u64);
+    fv!(f64, Value::Double(x) => x);
+    fv!(f32, Value::Double(x) => x as f32);
+    fv!(bool, Value::Int(x) => x != 0);
+    fv!(String, Value::Bytes(x) => String::from_utf8(x).unwrap());
+    fv!(Value, x => x);
+    pub struct OptIr<T>(Option<T>, Value);
+    impl<T: FromValue> ConvIr<Option<T>> for OptIr<T> {
+        fn new(v: Value) -> std::result::Result<Self, FromValueError> {
+            let raw = v.clone();
+            match v {
+                Value::NULL => Ok(OptIr(None, raw)),
+                v => T::from_value_opt(v).map(|x| OptIr(Some(x), raw)),
+            }
+        }
+        fn commit(self) -> Option<T> {
+            self.0
+        }
+        fn rollback(self) -> Value {
+            self.1
+        }
+    }
+    impl<T: FromValue> FromValue for Option<T> {
+        type Intermediate = OptIr<T>;
+    }
+    pub trait FromRow: Sized {
+        fn from_row(row: Row) -> Self {
+            Self::from_row_opt(row).ok().unwrap()
+        }
+        fn from_row_opt(row: Row) -> std::result::Result<Self, FromRowError>;
+    }
+    impl FromRow for Row {
+        fn from_row_opt(row: Row) -> std::result::Result<Self, FromRowError> {
+            Ok(row)
+        }
+    }
+    impl<T: FromValue> FromRow for (T,) {
+        fn from_row_opt(mut row: Row) -> std::result::Result<Self, FromRowError> {
+            Ok((row.take(0).unwrap(),))
+        }
+    }
+    impl<A: FromValue, B: FromValue, C: FromValue> FromRow for (A, B, C) {
+        fn from_row_opt(mut row: Row) -> std::result::Result<Self, FromRowError> {
+            Ok((row.take(0).unwrap(), row.take(1).unwrap(), row.take(2).unwrap()))
+        }
+    }
+    impl<A: FromValue, B: FromValue> FromRow for (A, B) {
+        fn from_row_opt(mut row: Row) -> std::result::Result<Self, FromRowError> {
+            Ok((row.take(0).unwrap(), row.take(1).unwrap()))
+        }
+    }
+    pub trait Queryable {
+        fn query_drop<Q: AsRef<str>>(&mut self, _q: Q) -> Result<()>;
+        fn query<T: FromRow, Q: AsRef<str>>(&mut self, _q: Q) -> Result<Vec<T>>;
+        fn query_first<T: FromRow, Q: AsRef<str>>(&mut self, _q: Q) -> Result<Option<T>>;
+        fn exec_drop<S: AsRef<str>, P: Into<Params>>(&mut self, _s: S, p: P) -> Result<()>;
+        fn exec<T: FromRow, S: AsRef<str>, P: Into<Params>>(&mut self, _s: S, p: P) -> Result<Vec<T>>;
+        fn exec_first<T: FromRow, S: AsRef<str>, P: Into<Params>>(&mut self, _s: S, p: P) -> Result<Option<T>>;
+        fn exec_batch<S: AsRef<str>, P: Into<Params>, I: IntoIterator<Item = P>>(&mut self, _s: S, p: I) -> Result<()>;
+    }
+}
+pub struct Conn;
+impl Conn {
+    pub fn last_insert_id(&self) -> u64 {
+        0
+    }
+    pub fn affected_rows(&self) -> u64 {
+        0
+    }
+    pub fn start_transaction(&mut self, _opts: TxOpts) -> Result<Transaction<'_>> {
+        run("START TRANSACTION")?;
+        Ok(Transaction { conn: self, done: false })
+    }
+}
+#[derive(Clone, Copy, Debug, Default)]
+pub struct TxOpts;
+/// 未 commit 即 drop 时回滚
+pub struct Transaction<'a> {
+    conn: &'a mut Conn,
+    done: bool,
+}
+impl<'a> Transaction<'a> {
+    pub fn last_insert_id(&self) -> Option<u64> {
+        None
+    }
+    pub fn affected_rows(&self) -> u64 {
+        0
+    }
+    pub fn commit(mut self) -> Result<()> {
+        self.done = true;
+        run("COMMIT")
+    }
+}
+impl Drop for Transaction<'_> {
+    fn drop(&mut self) {
+        if !self.done {
+            let _ = run("ROLLBACK");
+        }
+    }
+}
+thread_local! {
+    static LOG: std::cell::RefCell<Vec<String>> = std::cell::RefCell::new(Vec::new());
+    static ROWS: std::cell::RefCell<std::collections::VecDeque<Vec<Row>>> = std::cell::RefCell::new(Default::default());
+    static FAIL: std::cell::RefCell<Option<String>> = std::cell::RefCell::new(None);
+}
+/// 排队一个结果集, 由下一次 query/query_first/exec/exec_first 返回
+pub fn push_rows(names: &[&str], rows: Vec<Vec<Value>>) {
+    let rows = rows.into_iter().map(|values| Row::new(names, values)).collect();
+    ROWS.with(|x| x.borrow_mut().push_back(rows));
+}
+/// 之后含 pattern 的语句出错
+pub fn fail_on(pattern: &str) {
+    FAIL.with(|x| *x.borrow_mut() = Some(pattern.to_string()));
+}
+/// 取出已执行的语句, 并清空结果集队列及 fail_on
+pub fn take_log() -> Vec<String> {
+    ROWS.with(|x| x.borrow_mut().clear());
+    FAIL.with(|x| *x.borrow_mut() = None);
+    LOG.with(|x| std::mem::take(&mut *x.borrow_mut()))
+}
+fn run(sql: &str) -> Result<()> {
+    LOG.with(|x| x.borrow_mut().push(sql.to_string()));
+    match FAIL.with(|x| x.borrow().as_ref().map(|pattern| sql.contains(pattern.as_str()))) {
+        Some(true) => Err(Error),
+        _ => Ok(()),
+    }
+}
+fn fetch<T: prelude::FromRow>(sql: &str) -> Result<Vec<T>> {
+    run(sql)?;
+    let rows = ROWS.with(|x| x.borrow_mut().pop_front()).unwrap_or_default();
+    Ok(rows.into_iter().map(T::from_row).collect())
+}
+macro_rules! q {
+    ($t:ty) => {
+        impl prelude::Queryable for $t {
+            fn query_drop<Q: AsRef<str>>(&mut self, q: Q) -> Result<()> {
+                run(q.as_ref())
+            }
+            fn query<T: prelude::FromRow, Q: AsRef<str>>(&mut self, q: Q) -> Result<Vec<T>> {
+                fetch(q.as_ref())
+            }
+            fn query_first<T: prelude::FromRow, Q: AsRef<str>>(&mut self, q: Q) -> Result<Option<T>> {
+                Ok(fetch(q.as_ref())?.into_iter().next())
+            }
+            fn exec_drop<S: AsRef<str>, P: Into<Params>>(&mut self, s: S, _p: P) -> Result<()> {
+                run(s.as_ref())
+            }
+            fn exec<T: prelude::FromRow, S: AsRef<str>, P: Into<Params>>(&mut self, s: S, _p: P) -> Result<Vec<T>> {
+                fetch(s.as_ref())
+            }
+            fn exec_first<T: prelude::FromRow, S: AsRef<str>, P: Into<Params>>(
+                &mut self,
+                s: S,
+                _p: P,
+            ) -> Result<Option<T>> {
+                Ok(fetch(s.as_ref())?.into_iter().next())
+            }
+            fn exec_batch<S: AsRef<str>, P: Into<Params>, I: IntoIterator<Item = P>>(
+                &mut self,
+                s: S,
+                p: I,
+            ) -> Result<()> {
+                p.into_iter().try_for_each(|_| run(s.as_ref()))
+            }
+        }
+    };
+}
+q!(Conn);
+q!(Transaction<'_>);
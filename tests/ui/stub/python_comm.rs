@@ -0,0 +1,94 @@
+// 仿 python_comm 的最小 API, 配合 #[table(crate = "crate::stub")] 使用
+#![allow(dead_code)]
+
+macro_rules! m {
+    ($x:expr) => {
+        format!("{}:{}", file!(), $x)
+    };
+}
+pub(crate) use m;
+
+pub mod use_m {
+    #[derive(Debug)]
+    pub struct MoreError(pub String);
+
+    impl std::fmt::Display for MoreError {
+        fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+            write!(f, "{}", self.0)
+        }
+    }
+
+    pub trait MoreErrorForResult<T> {
+        fn m(self, s: String) -> Result<T, MoreError>;
+    }
+
+    impl<T, E: std::fmt::Display> MoreErrorForResult<T> for Result<T, E> {
+        fn m(self, s: String) -> Result<T, MoreError> {
+            self.map_err(|e| MoreError(format!("{} {}", s, e)))
+        }
+    }
+}
+
+pub mod use_sql {
+    use super::use_m::MoreError;
+
+    pub struct DbPool {
+        pub conn: crate::mysql::Conn,
+    }
+
+    impl DbPool {
+        pub fn get_conn(&mut self) -> Result<&mut crate::mysql::Conn, MoreError> {
+            Ok(&mut self.conn)
+        }
+    }
+
+    pub struct Page<T> {
+        pub items: Vec<T>,
+        pub total: u64,
+        pub page: u64,
+        pub per_page: u64,
+        pub pages: u64,
+    }
+
+    #[derive(Debug)]
+    pub struct ValidationError {
+        pub column: Option<&'static str>,
+        pub message: String,
+    }
+
+    impl std::fmt::Display for ValidationError {
+        fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+            match self.column {
+                Some(column) => write!(f, "{}: {}", column, self.message),
+                None => write!(f, "{}", self.message),
+            }
+        }
+    }
+
+    pub trait SqlModel: Sized {
+        fn create(&self) -> Result<Option<u64>, MoreError> {
+            Ok(None)
+        }
+        fn update(&self) -> Result<(), MoreError> {
+            Ok(())
+        }
+        fn delete(&self) -> Result<(), MoreError> {
+            Ok(())
+        }
+        fn equal(&self, other: &Self) -> bool;
+        fn equal_without_id(&self, other: &Self) -> bool;
+        fn lock() -> Result<std::sync::MutexGuard<'static, DbPool>, MoreError>;
+        fn make_create_table() -> &'static str;
+        fn make_fields_b() -> &'static str;
+        fn make_fields_bi() -> &'static str;
+        fn make_fields_e() -> &'static str;
+        fn make_fields_ei() -> &'static str;
+        fn make_fields_p() -> &'static str;
+        fn make_fields_pi() -> &'static str;
+        fn make_fields_q() -> &'static str;
+        fn make_fields_qc() -> &'static str;
+        fn make_fields_v(&self) -> crate::mysql::params::Params;
+        fn make_fields_vi(&self) -> crate::mysql::params::Params;
+        fn table_name() -> &'static str;
+    }
+}